@@ -8,28 +8,13 @@ use molint::{
 use nalgebra::Point3;
 
 const HYDROGEN_ATOMS: &[Atom] = &[
-    Atom {
-        ordinal: 1,
-        position: Point3::new(0.0, 0.0, 0.0),
-    },
-    Atom {
-        ordinal: 1,
-        position: Point3::new(0.0, 0.0, 1.4),
-    },
+    Atom::new(1, Point3::new(0.0, 0.0, 0.0)),
+    Atom::new(1, Point3::new(0.0, 0.0, 1.4)),
 ];
 const WATER_ATOMS: &[Atom] = &[
-    Atom {
-        ordinal: 1,
-        position: Point3::new(0.4175, 0.0, 0.83),
-    },
-    Atom {
-        ordinal: 8,
-        position: Point3::new(0.0, 0.0, -0.31),
-    },
-    Atom {
-        ordinal: 1,
-        position: Point3::new(-0.4175, 0.0, 0.83),
-    },
+    Atom::new(1, Point3::new(0.4175, 0.0, 0.83)),
+    Atom::new(8, Point3::new(0.0, 0.0, -0.31)),
+    Atom::new(1, Point3::new(-0.4175, 0.0, 0.83)),
 ];
 
 macro_rules! integral_bench {
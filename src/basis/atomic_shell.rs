@@ -0,0 +1,35 @@
+use super::ContractedGaussian;
+
+/// The way the angular part of the basis functions of a shell is represented.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FunctionType {
+    /// Cartesian polynomials x^i y^j z^k with i + j + k = l. There are (l + 1)(l + 2) / 2 of these
+    /// per shell.
+    Cartesian,
+    /// Real solid harmonics, of which there are 2l + 1 per shell.
+    Spherical,
+}
+
+impl FunctionType {
+    /// Returns the number of basis functions a shell of this type with the given angular momentum
+    /// magnitude contains.
+    pub(crate) fn count(self, angular_magnitude: i32) -> usize {
+        let l = angular_magnitude as usize;
+        match self {
+            FunctionType::Cartesian => (l + 1) * (l + 2) / 2,
+            FunctionType::Spherical => 2 * l + 1,
+        }
+    }
+}
+
+/// Represents a single contraction of some angular momentum magnitude in the basis of an element.
+///
+/// Independent of the [FunctionType], integrals are always evaluated over the cartesian components
+/// of the shell. Shells of [FunctionType::Spherical] are only transformed afterwards.
+#[derive(Clone, Debug)]
+pub(crate) struct AtomicShell {
+    pub(crate) angular_magnitude: i32,
+    pub(crate) function_type: FunctionType,
+    /// The cartesian components of this shell
+    pub(crate) basis: Vec<ContractedGaussian>,
+}
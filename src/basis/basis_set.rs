@@ -2,10 +2,14 @@ use std::{collections::HashMap, fs::File, path::Path};
 
 use crate::{periodic_table::ElementType, system::Atom};
 
-use super::{bse_basis_set::BseBasisSet, ContractedGaussian};
+use super::{bse_basis_set::BseBasisSet, AtomicShell, Ecp};
 
 /// This type represents a basis set that can be used as a basis in the integral evaluation.
-pub struct BasisSet(pub(crate) HashMap<ElementType, Vec<ContractedGaussian>>);
+pub struct BasisSet {
+    pub(crate) shells: HashMap<ElementType, Vec<AtomicShell>>,
+    /// The effective core potentials of the elements that use one
+    pub(crate) ecps: HashMap<ElementType, Ecp>,
+}
 
 impl BasisSet {
     /// Given a path, this function tries to load a basis set from a json file with the format that
@@ -15,10 +19,25 @@ impl BasisSet {
         basis_set.try_into()
     }
 
-    /// Returns the basis functions for the given atom.
-    pub(crate) fn atomic_basis(&self, atom: &Atom) -> &[ContractedGaussian] {
+    /// Returns the shells of basis functions for the given atom, which are empty for bare point
+    /// charges.
+    pub(crate) fn atomic_basis(&self, atom: &Atom) -> &[AtomicShell] {
+        if !atom.has_basis() {
+            return &[];
+        }
+
         let element_type = ElementType::from_ordinal(atom.ordinal)
             .unwrap_or_else(|| panic!("failed to convert ordinal {} to ElementType", atom.ordinal));
-        &self.0[&element_type]
+        &self.shells[&element_type]
+    }
+
+    /// Returns the effective core potential of the given atom, if its element has one. Ghost atoms,
+    /// point charges and atoms with a custom charge don't use effective core potentials.
+    pub(crate) fn ecp(&self, atom: &Atom) -> Option<&Ecp> {
+        if !atom.has_basis() || atom.charge().is_some() {
+            return None;
+        }
+
+        self.ecps.get(&ElementType::from_ordinal(atom.ordinal)?)
     }
 }
@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 
+use anyhow::{bail, ensure, Context};
+use itertools::Itertools;
 use serde::Deserialize;
 
 use crate::periodic_table::ElementType;
 
-use super::{BasisSet, ContractedGaussian};
+use super::{AtomicShell, BasisSet, ContractedGaussian, Ecp, EcpTerm, FunctionType};
 
 /// Helper type to correctly deserialize a full basis set in the basis set exchange json format
 #[derive(Deserialize, Debug)]
@@ -17,6 +19,10 @@ pub(super) struct BseBasisSet {
 #[derive(Deserialize, Debug)]
 struct BseElectronicConfiguration {
     electron_shells: Vec<BseElectronShell>,
+    #[serde(default)]
+    ecp_electrons: usize,
+    #[serde(default)]
+    ecp_potentials: Vec<BseEcpPotential>,
 }
 
 /// Helper type to correctly deserialize basis set exchange electron shells of a specific atom in a
@@ -30,82 +36,158 @@ struct BseElectronShell {
     coefficients: Vec<Vec<String>>,
 }
 
+/// Helper type to correctly deserialize the radial potentials of the effective core potential of a
+/// specific atom in a [BseElectronicConfiguration]
+#[derive(Deserialize, Debug)]
+struct BseEcpPotential {
+    ecp_type: String,
+    angular_momentum: Vec<i32>,
+    /// The powers of r plus 2, i.e., 2 denotes a plain Gaussian
+    r_exponents: Vec<i32>,
+    gaussian_exponents: Vec<String>,
+    coefficients: Vec<Vec<String>>,
+}
+
 impl TryFrom<BseBasisSet> for BasisSet {
     // TODO(style): use a "better" type for error
     type Error = anyhow::Error;
 
     fn try_from(value: BseBasisSet) -> Result<Self, Self::Error> {
         let mut atomic_mapping = HashMap::with_capacity(value.elements.len());
+        let mut ecps = HashMap::new();
 
         // TODO(style): this is pretty deeply nested, this can definitely be improved somehow
         for (element, configuration) in value.elements {
             let mut element_basis = Vec::new();
 
             for electron_shell in &configuration.electron_shells {
-                match electron_shell.function_type.as_str() {
-                    "gto" | "gto_cartesian" => {
-                        // these are equivalent and can be computed using the current integral
-                        // implementation. Angular momentum is represented as cartesian
-                        // polynomials.
-                    }
-                    "gto_spherical" => {
-                        // In contrast to "gto" and "gto_cartesian", basis functions are
-                        // (supposed to be) represented as spherical harmonics. The current
-                        // implementation, however, does not support integrating over these.
-
-                        // TODO(completeness): implement conversion function from "gto_spherical"
-                        //  to "gto_cartesian"
-                        log::warn!(
-                            r#"skipping unsupported basis function type "gto_spherical" on element {element:?}"#
-                        );
-                        continue;
-                    }
+                let function_type = match electron_shell.function_type.as_str() {
+                    // these are equivalent. Angular momentum is represented as cartesian
+                    // polynomials.
+                    "gto" | "gto_cartesian" => FunctionType::Cartesian,
+                    // Angular momentum is represented as real solid harmonics. Integrals are still
+                    // evaluated over the cartesian components and transformed afterwards.
+                    "gto_spherical" => FunctionType::Spherical,
                     function_type => {
                         log::warn!("skipping unknown basis function type {function_type} on element {element:?}");
                         continue;
                     }
-                }
-
-                for (index, &angular_magnitude) in
-                    electron_shell.angular_momentum.iter().enumerate()
-                {
-                    let angular_vectors = generate_angular_vectors(angular_magnitude);
-
-                    for angular in angular_vectors {
-                        let mut exponents = Vec::with_capacity(electron_shell.exponents.len());
-                        let mut coefficients =
-                            Vec::with_capacity(electron_shell.coefficients.len());
-
-                        for (exponent, coefficient) in electron_shell
-                            .exponents
-                            .iter()
-                            .zip(&electron_shell.coefficients[index])
-                        {
-                            let exponent = exponent.parse::<f64>()?;
-                            let coefficient = coefficient.parse::<f64>()?;
-
-                            let norm = gaussian_norm(exponent, angular);
-
-                            exponents.push(exponent);
-                            coefficients.push(norm * coefficient);
-                        }
-
-                        element_basis.push(ContractedGaussian {
-                            coefficients: coefficients.into(),
-                            exponents: exponents.into(),
+                };
+
+                let exponents: Vec<f64> = electron_shell
+                    .exponents
+                    .iter()
+                    .map(|exponent| exponent.parse::<f64>())
+                    .try_collect()?;
+
+                // Usually, there is one angular momentum per list of coefficients (e.g., SP shells).
+                // General contractions, however, list a single angular momentum for several lists
+                // of coefficients.
+                for (index, coefficients) in electron_shell.coefficients.iter().enumerate() {
+                    let angular_magnitude = match electron_shell.angular_momentum[..] {
+                        [angular_magnitude] => angular_magnitude,
+                        ref angular_momentum => angular_momentum[index],
+                    };
+
+                    // s and p shells are the same in both representations
+                    let function_type = if angular_magnitude < 2 {
+                        FunctionType::Cartesian
+                    } else {
+                        function_type
+                    };
+
+                    let coefficients: Vec<f64> = coefficients
+                        .iter()
+                        .map(|coefficient| coefficient.parse::<f64>())
+                        .try_collect()?;
+
+                    let basis = generate_angular_vectors(angular_magnitude)
+                        .into_iter()
+                        .map(|angular| ContractedGaussian {
+                            coefficients: exponents
+                                .iter()
+                                .zip(&coefficients)
+                                .map(|(&exponent, &coefficient)| {
+                                    gaussian_norm(exponent, angular) * coefficient
+                                })
+                                .collect(),
+                            exponents: exponents.iter().copied().collect(),
                             angular,
-                        });
-                    }
+                        })
+                        .collect();
+
+                    element_basis.push(AtomicShell {
+                        angular_magnitude,
+                        function_type,
+                        basis,
+                    });
                 }
             }
 
+            if configuration.ecp_electrons > 0 {
+                ecps.insert(element, parse_ecp(&configuration)?);
+            }
             atomic_mapping.insert(element, element_basis);
         }
 
-        Ok(Self(atomic_mapping))
+        Ok(Self {
+            shells: atomic_mapping,
+            ecps,
+        })
     }
 }
 
+/// Converts the potentials of an electronic configuration into an [Ecp]. The potential with the
+/// highest angular momentum is the local one, all others are semi-local and already given relative
+/// to the local one.
+fn parse_ecp(configuration: &BseElectronicConfiguration) -> anyhow::Result<Ecp> {
+    let mut potentials = Vec::with_capacity(configuration.ecp_potentials.len());
+    for potential in &configuration.ecp_potentials {
+        if potential.ecp_type != "scalar_ecp" {
+            log::warn!("skipping unknown ECP type {}", potential.ecp_type);
+            continue;
+        }
+
+        let [angular_magnitude] = potential.angular_momentum[..] else {
+            bail!("expected a single angular momentum per ECP potential");
+        };
+        let [ref coefficients] = potential.coefficients[..] else {
+            bail!("expected a single list of coefficients per ECP potential");
+        };
+        ensure!(
+            potential.r_exponents.len() == potential.gaussian_exponents.len()
+                && coefficients.len() == potential.gaussian_exponents.len(),
+            "the ECP potential with angular momentum {angular_magnitude} has inconsistent lengths"
+        );
+
+        let terms = potential
+            .r_exponents
+            .iter()
+            .zip(&potential.gaussian_exponents)
+            .zip(coefficients)
+            .map(|((&r_exponent, exponent), coefficient)| {
+                Ok(EcpTerm {
+                    power: r_exponent - 2,
+                    exponent: exponent.parse()?,
+                    coefficient: coefficient.parse()?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        potentials.push((angular_magnitude, terms));
+    }
+
+    potentials.sort_by_key(|&(angular_magnitude, _)| angular_magnitude);
+    let (_, local) = potentials
+        .pop()
+        .context("missing the local ECP potential")?;
+
+    Ok(Ecp {
+        core_electrons: configuration.ecp_electrons,
+        local,
+        semi_local: potentials,
+    })
+}
+
 // generate all (i, j, k) such that i + j + k = angular
 fn generate_angular_vectors(angular_magnitude: i32) -> Vec<[i32; 3]> {
     let mut angular_vectors = Vec::with_capacity(8);
@@ -1,3 +1,4 @@
+use nalgebra::Point3;
 use smallvec::SmallVec;
 
 /// Represents a single contracted gaussian basis function.
@@ -21,4 +22,43 @@ impl ContractedGaussian {
             .copied()
             .zip(self.exponents.iter().copied())
     }
+
+    /// Evaluates this function, centered at the given position, at the given point.
+    pub fn value(&self, center: &Point3<f64>, point: &Point3<f64>) -> f64 {
+        let diff = point - center;
+        let polynomial: f64 = (0..3)
+            .map(|axis| diff[axis].powi(self.angular[axis]))
+            .product();
+        let distance_squared = diff.norm_squared();
+        let radial: f64 = self
+            .iter()
+            .map(|(coefficient, exponent)| coefficient * (-exponent * distance_squared).exp())
+            .sum();
+        polynomial * radial
+    }
+
+    /// Returns the terms of the derivative of this function with respect to its center along the
+    /// given axis, which are again [ContractedGaussian]s:
+    ///     ∂/∂A_i G(l) = 2a G(l + 1_i) - l_i G(l - 1_i)
+    pub(crate) fn center_derivative(&self, axis: usize) -> SmallVec<[ContractedGaussian; 2]> {
+        let mut terms = SmallVec::new();
+
+        let mut raised = self.clone();
+        raised.angular[axis] += 1;
+        for (coefficient, exponent) in raised.coefficients.iter_mut().zip(&self.exponents) {
+            *coefficient *= 2.0 * exponent;
+        }
+        terms.push(raised);
+
+        if self.angular[axis] > 0 {
+            let mut lowered = self.clone();
+            lowered.angular[axis] -= 1;
+            for coefficient in &mut lowered.coefficients {
+                *coefficient *= -self.angular[axis] as f64;
+            }
+            terms.push(lowered);
+        }
+
+        terms
+    }
 }
@@ -0,0 +1,37 @@
+/// A single term d r^n exp(-ζ r^2) of the radial potential of an [Ecp]
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct EcpTerm {
+    /// The power n of r, which is usually 0
+    pub(crate) power: i32,
+    pub(crate) exponent: f64,
+    pub(crate) coefficient: f64,
+}
+
+impl EcpTerm {
+    /// Evaluates this term at the given distance to the center of the [Ecp].
+    pub(crate) fn value(&self, r: f64) -> f64 {
+        self.coefficient * r.powi(self.power) * (-self.exponent * r * r).exp()
+    }
+}
+
+/// An effective core potential, which replaces the innermost electrons of an atom. It consists of
+/// a local potential U_L and semi-local potentials, which only act on the angular momentum
+/// components l < L of a function around the atom:
+///     U = U_L(r) + Σ_{l<L} Σ_m |lm⟩ (U_l(r) - U_L(r)) ⟨lm|
+#[derive(Clone, Debug)]
+pub(crate) struct Ecp {
+    /// The number of electrons that are replaced by this potential, which is subtracted from the
+    /// nuclear charge
+    pub(crate) core_electrons: usize,
+    /// The terms of U_L
+    pub(crate) local: Vec<EcpTerm>,
+    /// The terms of U_l - U_L, by l
+    pub(crate) semi_local: Vec<(i32, Vec<EcpTerm>)>,
+}
+
+impl Ecp {
+    /// Returns the highest angular momentum of a semi-local potential, or -1 if there is none.
+    pub(crate) fn max_angular_magnitude(&self) -> i32 {
+        self.semi_local.iter().map(|&(l, _)| l).max().unwrap_or(-1)
+    }
+}
@@ -1,8 +1,13 @@
 //! This module contains types that are associated with either basis functions or with basis sets.
 
+mod atomic_shell;
 mod basis_set;
 pub(super) mod bse_basis_set;
 mod contracted_gaussian;
+mod ecp;
 
+pub(crate) use atomic_shell::AtomicShell;
+pub use atomic_shell::FunctionType;
 pub use basis_set::BasisSet;
 pub use contracted_gaussian::ContractedGaussian;
+pub(crate) use ecp::{Ecp, EcpTerm};
@@ -0,0 +1,138 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A dual number with the partial derivatives with respect to the density and its squared gradient
+/// norm, which gives the potentials of the functionals by forward-mode differentiation.
+#[derive(Copy, Clone, Debug)]
+pub(super) struct Dual {
+    pub(super) value: f64,
+    pub(super) derivatives: [f64; 2],
+}
+
+impl Dual {
+    pub(super) fn constant(value: f64) -> Self {
+        Dual {
+            value,
+            derivatives: [0.0; 2],
+        }
+    }
+
+    /// Returns the variable with the given index (0 for the density, 1 for the squared gradient
+    /// norm) and the given value.
+    pub(super) fn variable(value: f64, index: usize) -> Self {
+        let mut derivatives = [0.0; 2];
+        derivatives[index] = 1.0;
+        Dual { value, derivatives }
+    }
+
+    /// Applies a function with the given value and derivative at this number (chain rule).
+    pub(super) fn chain(self, value: f64, derivative: f64) -> Self {
+        Dual {
+            value,
+            derivatives: self.derivatives.map(|d| derivative * d),
+        }
+    }
+
+    pub(super) fn ln(self) -> Self {
+        self.chain(self.value.ln(), self.value.recip())
+    }
+
+    pub(super) fn exp(self) -> Self {
+        let exp = self.value.exp();
+        self.chain(exp, exp)
+    }
+
+    pub(super) fn sqrt(self) -> Self {
+        let sqrt = self.value.sqrt();
+        self.chain(sqrt, 0.5 / sqrt)
+    }
+
+    pub(super) fn powf(self, exponent: f64) -> Self {
+        self.chain(
+            self.value.powf(exponent),
+            exponent * self.value.powf(exponent - 1.0),
+        )
+    }
+
+    pub(super) fn atan(self) -> Self {
+        self.chain(self.value.atan(), 1.0 / (1.0 + self.value * self.value))
+    }
+
+    pub(super) fn recip(self) -> Self {
+        self.chain(self.value.recip(), -self.value.powi(-2))
+    }
+}
+
+impl Add for Dual {
+    type Output = Dual;
+
+    fn add(self, other: Dual) -> Dual {
+        Dual {
+            value: self.value + other.value,
+            derivatives: [0, 1].map(|i| self.derivatives[i] + other.derivatives[i]),
+        }
+    }
+}
+
+impl Sub for Dual {
+    type Output = Dual;
+
+    fn sub(self, other: Dual) -> Dual {
+        self + -other
+    }
+}
+
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl Mul for Dual {
+    type Output = Dual;
+
+    fn mul(self, other: Dual) -> Dual {
+        Dual {
+            value: self.value * other.value,
+            derivatives: [0, 1]
+                .map(|i| self.derivatives[i] * other.value + self.value * other.derivatives[i]),
+        }
+    }
+}
+
+#[allow(clippy::suspicious_arithmetic_impl)]
+impl Div for Dual {
+    type Output = Dual;
+
+    fn div(self, other: Dual) -> Dual {
+        self * other.recip()
+    }
+}
+
+impl Neg for Dual {
+    type Output = Dual;
+
+    fn neg(self) -> Dual {
+        self.chain(-self.value, -1.0)
+    }
+}
+
+/// Implements an arithmetic operator between [Dual] and f64 in both orders.
+macro_rules! scalar_operator {
+    ($trait:ident, $method:ident) => {
+        impl $trait<f64> for Dual {
+            type Output = Dual;
+
+            fn $method(self, other: f64) -> Dual {
+                self.$method(Dual::constant(other))
+            }
+        }
+
+        impl $trait<Dual> for f64 {
+            type Output = Dual;
+
+            fn $method(self, other: Dual) -> Dual {
+                Dual::constant(self).$method(other)
+            }
+        }
+    };
+}
+
+scalar_operator!(Add, add);
+scalar_operator!(Sub, sub);
+scalar_operator!(Mul, mul);
+scalar_operator!(Div, div);
@@ -0,0 +1,326 @@
+use std::f64::consts::{LN_2, PI};
+
+use super::dual::Dual;
+
+/// Densities below this are treated as zero, where the functionals are not well-defined
+pub(super) const DENSITY_CUTOFF: f64 = 1e-12;
+
+/// An exchange-correlation functional for closed-shell Kohn-Sham calculations.
+///
+/// # References
+///
+/// [1] Slater, J. C. A simplification of the Hartree-Fock method. Phys. Rev. 1951, 81, 385–390
+///
+/// [2] Vosko, S. H.; Wilk, L.; Nusair, M. Accurate spin-dependent electron liquid correlation
+/// energies for local spin density calculations: a critical analysis. Can. J. Phys. 1980, 58,
+/// 1200–1211
+///
+/// [3] Becke, A. D. Density-functional exchange-energy approximation with correct asymptotic
+/// behavior. Phys. Rev. A 1988, 38, 3098–3100
+///
+/// [4] Lee, C.; Yang, W.; Parr, R. G. Development of the Colle-Salvetti correlation-energy formula
+/// into a functional of the electron density. Phys. Rev. B 1988, 37, 785–789
+///
+/// [5] Perdew, J. P.; Wang, Y. Accurate and simple analytic representation of the electron-gas
+/// correlation energy. Phys. Rev. B 1992, 45, 13244–13249
+///
+/// [6] Perdew, J. P.; Burke, K.; Ernzerhof, M. Generalized gradient approximation made simple.
+/// Phys. Rev. Lett. 1996, 77, 3865–3868
+///
+/// [7] Stephens, P. J.; Devlin, F. J.; Chabalowski, C. F.; Frisch, M. J. Ab initio calculation of
+/// vibrational absorption and circular dichroism spectra using density functional force fields.
+/// J. Phys. Chem. 1994, 98, 11623–11627
+///
+/// [8] Adamo, C.; Barone, V. Toward reliable density functional methods without adjustable
+/// parameters: the PBE0 model. J. Chem. Phys. 1999, 110, 6158–6170
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Functional {
+    /// The local density approximation with Slater exchange and the VWN5 correlation functional
+    Svwn5,
+    /// Becke 88 exchange and Lee-Yang-Parr correlation
+    Blyp,
+    /// The PBE exchange and correlation functionals, with the PW92 correlation of the uniform
+    /// electron gas
+    Pbe,
+    /// 20% exact exchange, 8% Slater and 72% Becke 88 exchange, 19% VWN and 81% LYP correlation,
+    /// with the RPA parametrization of VWN as in Gaussian
+    B3lyp,
+    /// 25% exact exchange, 75% PBE exchange and PBE correlation
+    Pbe0,
+}
+
+/// The parts that the functionals are built from
+#[derive(Copy, Clone, Debug)]
+enum Component {
+    Slater,
+    Becke88,
+    PbeExchange,
+    Vwn5,
+    VwnRpa,
+    Lyp,
+    PbeCorrelation,
+}
+
+impl Functional {
+    /// Returns the fraction of exact (Hartree-Fock) exchange.
+    pub fn exact_exchange(&self) -> f64 {
+        match self {
+            Functional::B3lyp => 0.2,
+            Functional::Pbe0 => 0.25,
+            _ => 0.0,
+        }
+    }
+
+    /// Returns whether the functional depends on the gradient of the density.
+    pub fn is_gga(&self) -> bool {
+        !matches!(self, Functional::Svwn5)
+    }
+
+    /// Returns the components of this functional with their coefficients.
+    fn components(&self) -> &'static [(Component, f64)] {
+        match self {
+            Functional::Svwn5 => &[(Component::Slater, 1.0), (Component::Vwn5, 1.0)],
+            Functional::Blyp => &[(Component::Becke88, 1.0), (Component::Lyp, 1.0)],
+            Functional::Pbe => &[
+                (Component::PbeExchange, 1.0),
+                (Component::PbeCorrelation, 1.0),
+            ],
+            Functional::B3lyp => &[
+                (Component::Slater, 0.08),
+                (Component::Becke88, 0.72),
+                (Component::VwnRpa, 0.19),
+                (Component::Lyp, 0.81),
+            ],
+            Functional::Pbe0 => &[
+                (Component::PbeExchange, 0.75),
+                (Component::PbeCorrelation, 1.0),
+            ],
+        }
+    }
+
+    /// Returns the energy per volume of a closed-shell density with the value rho and the squared
+    /// gradient norm sigma, along with its derivatives with respect to rho and sigma. Densities
+    /// below [DENSITY_CUTOFF] give zero.
+    pub(super) fn evaluate(&self, rho: f64, sigma: f64) -> (f64, [f64; 2]) {
+        if rho < DENSITY_CUTOFF {
+            return (0.0, [0.0; 2]);
+        }
+        let rho = Dual::variable(rho, 0);
+        let sigma = Dual::variable(sigma.max(0.0), 1);
+        let energy = self.components().iter().fold(
+            Dual::constant(0.0),
+            |energy, &(component, coefficient)| {
+                energy + coefficient * component.evaluate(rho, sigma)
+            },
+        );
+        (energy.value, energy.derivatives)
+    }
+}
+
+impl Component {
+    fn evaluate(&self, rho: Dual, sigma: Dual) -> Dual {
+        match self {
+            Component::Slater => slater(rho),
+            Component::Becke88 => becke88(rho, sigma),
+            Component::PbeExchange => pbe_exchange(rho, sigma),
+            Component::Vwn5 => vwn(rho, &VWN5),
+            Component::VwnRpa => vwn(rho, &VWN_RPA),
+            Component::Lyp => lyp(rho, sigma),
+            Component::PbeCorrelation => pbe_correlation(rho, sigma),
+        }
+    }
+}
+
+/// Returns the Slater exchange energy per volume -3/4 (3/π)^(1/3) ρ^(4/3).
+fn slater(rho: Dual) -> Dual {
+    -0.75 * (3.0 / PI).cbrt() * rho.powf(4.0 / 3.0)
+}
+
+/// Returns the Wigner-Seitz radius r_s = (3 / (4πρ))^(1/3).
+fn wigner_seitz_radius(rho: Dual) -> Dual {
+    (3.0 / (4.0 * PI)).cbrt() * rho.powf(-1.0 / 3.0)
+}
+
+/// The parameters A, b, c and x_0 of the paramagnetic VWN interpolation formulas
+struct VwnParameters([f64; 4]);
+
+/// The fit to the correlation energies of Ceperley and Alder (VWN5)
+const VWN5: VwnParameters = VwnParameters([0.0310907, 3.72744, 12.9352, -0.10498]);
+/// The fit to RPA correlation energies (VWN1RPA or VWN3)
+const VWN_RPA: VwnParameters = VwnParameters([0.0310907, 13.0720, 42.7198, -0.409286]);
+
+/// Returns the VWN correlation energy per volume of the unpolarized electron gas.
+fn vwn(rho: Dual, parameters: &VwnParameters) -> Dual {
+    let [a, b, c, x0] = parameters.0;
+    let x = wigner_seitz_radius(rho).sqrt();
+    let big_x = x * x + b * x + c;
+    let big_x0 = x0 * x0 + b * x0 + c;
+    let q = (4.0 * c - b * b).sqrt();
+    let atan = (q / (2.0 * x + b)).atan();
+
+    let epsilon = a
+        * ((x * x / big_x).ln() + 2.0 * b / q * atan
+            - b * x0 / big_x0
+                * (((x - x0) * (x - x0) / big_x).ln() + 2.0 * (b + 2.0 * x0) / q * atan));
+    rho * epsilon
+}
+
+/// Returns the PW92 correlation energy per particle of the unpolarized electron gas, with the
+/// value of A as in the reference implementation of PBE.
+fn pw92(rho: Dual) -> Dual {
+    const A: f64 = 0.0310907;
+    const ALPHA: f64 = 0.21370;
+    const BETA: [f64; 4] = [7.5957, 3.5876, 1.6382, 0.49294];
+
+    let rs = wigner_seitz_radius(rho);
+    let sqrt = rs.sqrt();
+    let denominator =
+        2.0 * A * (BETA[0] * sqrt + BETA[1] * rs + BETA[2] * rs * sqrt + BETA[3] * rs * rs);
+    -2.0 * A * (1.0 + ALPHA * rs) * (1.0 + denominator.recip()).ln()
+}
+
+/// The parameter β of the PBE correlation functional
+const PBE_BETA: f64 = 0.06672455060314922;
+
+/// Returns the PBE exchange energy per volume.
+fn pbe_exchange(rho: Dual, sigma: Dual) -> Dual {
+    const KAPPA: f64 = 0.804;
+    const MU: f64 = PBE_BETA * PI * PI / 3.0;
+
+    let fermi_wavevector = (3.0 * PI * PI * rho).powf(1.0 / 3.0);
+    let s2 = sigma / (4.0 * fermi_wavevector * fermi_wavevector * rho * rho);
+    let enhancement = 1.0 + KAPPA - KAPPA / (1.0 + MU / KAPPA * s2);
+    slater(rho) * enhancement
+}
+
+/// Returns the PBE correlation energy per volume.
+fn pbe_correlation(rho: Dual, sigma: Dual) -> Dual {
+    let gamma = (1.0 - LN_2) / (PI * PI);
+
+    let epsilon = pw92(rho);
+    let fermi_wavevector = (3.0 * PI * PI * rho).powf(1.0 / 3.0);
+    let screening_wavevector2 = 4.0 * fermi_wavevector / PI;
+    let t2 = sigma / (4.0 * screening_wavevector2 * rho * rho);
+    let a = PBE_BETA / gamma / ((-epsilon / gamma).exp() - 1.0);
+    let at2 = a * t2;
+    let gradient_correction =
+        gamma * (1.0 + PBE_BETA / gamma * t2 * (1.0 + at2) / (1.0 + at2 + at2 * at2)).ln();
+    rho * (epsilon + gradient_correction)
+}
+
+/// Returns the Becke 88 exchange energy per volume, including the Slater exchange.
+fn becke88(rho: Dual, sigma: Dual) -> Dual {
+    const BETA: f64 = 0.0042;
+
+    // the contributions of each spin with half the density
+    let rho_spin = 0.5 * rho;
+    let x2 = 0.25 * sigma / rho_spin.powf(8.0 / 3.0);
+    let slater_spin = 1.5 * (3.0 / (4.0 * PI)).cbrt();
+    2.0 * rho_spin.powf(4.0 / 3.0) * (-slater_spin - BETA * x2 / (1.0 + 6.0 * BETA * x_asinh_x(x2)))
+}
+
+/// Returns x asinh(x) as a function of x^2, which is smooth at x = 0.
+fn x_asinh_x(x2: Dual) -> Dual {
+    let x = x2.value.sqrt();
+    let derivative = if x2.value < 1e-8 {
+        1.0 - x2.value / 3.0
+    } else {
+        (x.asinh() + x / (1.0 + x2.value).sqrt()) / (2.0 * x)
+    };
+    x2.chain(x * x.asinh(), derivative)
+}
+
+/// Returns the LYP correlation energy per volume, in the form without the Laplacian of the density
+/// by Miehlich et al. for equal spin densities.
+///
+/// # References
+///
+/// [1] Miehlich, B.; Savin, A.; Stoll, H.; Preuss, H. Results obtained with the correlation energy
+/// density functionals of Becke and Lee, Yang and Parr. Chem. Phys. Lett. 1989, 157, 200–206
+fn lyp(rho: Dual, sigma: Dual) -> Dual {
+    const A: f64 = 0.04918;
+    const B: f64 = 0.132;
+    const C: f64 = 0.2533;
+    const D: f64 = 0.349;
+    let fermi_constant = 0.3 * (3.0 * PI * PI).powf(2.0 / 3.0);
+
+    let rho_13 = rho.powf(-1.0 / 3.0);
+    let denominator = 1.0 + D * rho_13;
+    let omega = (-C * rho_13).exp() / denominator * rho.powf(-11.0 / 3.0);
+    let delta = C * rho_13 + D * rho_13 / denominator;
+
+    // spin densities and squared gradient norms
+    let rho_spin = 0.5 * rho;
+    let sigma_spin = 0.25 * sigma;
+
+    let bracket = rho_spin
+        * rho_spin
+        * (2.0f64.powf(11.0 / 3.0) * fermi_constant * 2.0 * rho_spin.powf(8.0 / 3.0)
+            + (47.0 / 18.0 - 7.0 / 18.0 * delta) * sigma
+            - (2.5 - delta / 18.0) * 2.0 * sigma_spin
+            - (delta - 11.0) / 9.0 * sigma_spin)
+        - 2.0 / 3.0 * rho * rho * sigma
+        + 2.0 * (2.0 / 3.0 * rho * rho - rho_spin * rho_spin) * sigma_spin;
+    -4.0 * A / denominator * rho_spin * rho_spin / rho - A * B * omega * bracket
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Component, Dual, Functional};
+
+    #[test]
+    fn potentials() {
+        let functionals = [
+            Functional::Svwn5,
+            Functional::Blyp,
+            Functional::Pbe,
+            Functional::B3lyp,
+            Functional::Pbe0,
+        ];
+        for functional in functionals {
+            for (rho, sigma) in [(1e-4, 1e-9), (0.01, 1e-4), (0.3, 0.05), (20.0, 3000.0)] {
+                let (_, [v_rho, v_sigma]) = functional.evaluate(rho, sigma);
+                let energy = |rho, sigma| functional.evaluate(rho, sigma).0;
+
+                let h = 1e-5 * rho;
+                let expected = (energy(rho + h, sigma) - energy(rho - h, sigma)) / (2.0 * h);
+                approx::assert_relative_eq!(v_rho, expected, max_relative = 1e-7);
+
+                let h = 1e-5 * sigma;
+                let expected = (energy(rho, sigma + h) - energy(rho, sigma - h)) / (2.0 * h);
+                approx::assert_relative_eq!(v_sigma, expected, max_relative = 1e-6);
+                assert_eq!(v_sigma == 0.0, !functional.is_gga());
+            }
+        }
+    }
+
+    #[test]
+    fn uniform_density() {
+        // the gradient corrections vanish for a uniform density
+        for rho in [1e-3, 0.1, 10.0] {
+            let rho = Dual::variable(rho, 0);
+            let sigma = Dual::variable(0.0, 1);
+            let slater = Component::Slater.evaluate(rho, sigma);
+            for exchange in [Component::Becke88, Component::PbeExchange] {
+                let value = exchange.evaluate(rho, sigma);
+                approx::assert_relative_eq!(value.value, slater.value, max_relative = 1e-14);
+                approx::assert_relative_eq!(
+                    value.derivatives[0],
+                    slater.derivatives[0],
+                    max_relative = 1e-14
+                );
+            }
+
+            let pw92 = rho * super::pw92(rho);
+            let pbe = Component::PbeCorrelation.evaluate(rho, sigma);
+            approx::assert_relative_eq!(pbe.value, pw92.value, max_relative = 1e-14);
+
+            // VWN5 and PW92 are fits to the same correlation energies
+            let vwn5 = Component::Vwn5.evaluate(rho, sigma);
+            approx::assert_relative_eq!(vwn5.value, pw92.value, max_relative = 1e-2);
+        }
+
+        // the vanishing density gives zero instead of NaN
+        assert_eq!(Functional::Blyp.evaluate(0.0, 0.0), (0.0, [0.0; 2]));
+    }
+}
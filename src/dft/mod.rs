@@ -0,0 +1,129 @@
+//! This module contains exchange-correlation functionals and their numerical integration on
+//! molecular grids for Kohn-Sham density functional theory, see [crate::scf::rks].
+
+mod dual;
+mod functional;
+
+use nalgebra::DMatrix;
+
+use crate::{grid::MolecularGrid, storage::SymmetricMatrix, system::MolecularSystem};
+
+pub use functional::Functional;
+
+/// Returns the exchange-correlation energy of the given closed-shell (total) density matrix and
+/// the matrix of the exchange-correlation potential V_ab = ∂E_xc/∂D_ab, integrated on the given
+/// grid.
+pub fn exchange_correlation(
+    system: &MolecularSystem,
+    grid: &MolecularGrid,
+    functional: Functional,
+    density: &SymmetricMatrix,
+) -> (f64, SymmetricMatrix) {
+    let density = DMatrix::from(density);
+    let order = usize::from(functional.is_gga());
+    let chunks =
+        crate::integrals::values::map_chunks(system, &grid.points, order, |start, derivatives| {
+            let values = &derivatives[0];
+            let products = values * &density;
+
+            // the derivatives of the energy with respect to the basis function values of each
+            // point, up to a factor 2, which give V = Φ^T B + B^T Φ
+            let mut energy = 0.0;
+            let mut derivatives_values = DMatrix::zeros(values.nrows(), values.ncols());
+            for (i, products) in products.row_iter().enumerate() {
+                let rho = values.row(i).dot(&products);
+                if rho < functional::DENSITY_CUTOFF {
+                    continue;
+                }
+                let gradient = derivatives[1..]
+                    .iter()
+                    .map(|gradient| 2.0 * gradient.row(i).dot(&products));
+                let gradient: Vec<_> = gradient.collect();
+                let sigma = gradient.iter().map(|g| g * g).sum();
+
+                let weight = grid.weights[start + i];
+                let (value, [v_rho, v_sigma]) = functional.evaluate(rho, sigma);
+                energy += weight * value;
+
+                let mut row = derivatives_values.row_mut(i);
+                row += 0.5 * weight * v_rho * values.row(i);
+                for (g, gradient) in gradient.iter().zip(&derivatives[1..]) {
+                    row += 2.0 * weight * v_sigma * g * gradient.row(i);
+                }
+            }
+            let potential = values.transpose() * &derivatives_values;
+            (energy, &potential + potential.transpose())
+        });
+
+    let n_basis = system.n_basis();
+    let (energy, potential) = chunks.into_iter().fold(
+        (0.0, DMatrix::zeros(n_basis, n_basis)),
+        |(energy, potential), (chunk_energy, chunk_potential)| {
+            (energy + chunk_energy, potential + chunk_potential)
+        },
+    );
+    (energy, SymmetricMatrix::from(&potential))
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{DMatrix, Point3};
+
+    use crate::{
+        basis::BasisSet,
+        grid::{GridOptions, MolecularGrid},
+        storage::SymmetricMatrix,
+        system::{Atom, MolecularSystem},
+    };
+
+    use super::Functional;
+
+    #[test]
+    fn helium_energies() {
+        let basis_set = BasisSet::load("data/basis/6-311G.json").unwrap();
+        let system = MolecularSystem::from_atoms(&[Atom::new(2, Point3::origin())], &basis_set);
+        let rhf = crate::scf::rhf(&system, &Default::default()).unwrap();
+        let grid = MolecularGrid::new(&system, &GridOptions::default()).unwrap();
+
+        let energy =
+            |functional| super::exchange_correlation(&system, &grid, functional, &rhf.density).0;
+        // exchange and correlation energies of the Hartree-Fock density of helium from the
+        // literature (LSD -0.884 + -0.112, B88 -1.025 + LYP -0.044, PBE -1.014 + -0.042)
+        approx::assert_abs_diff_eq!(energy(Functional::Svwn5), -0.996, epsilon = 2e-3);
+        approx::assert_abs_diff_eq!(energy(Functional::Blyp), -1.069, epsilon = 2e-3);
+        approx::assert_abs_diff_eq!(energy(Functional::Pbe), -1.056, epsilon = 2e-3);
+    }
+
+    #[test]
+    fn potential_matrix() {
+        let basis_set = BasisSet::load("data/basis/6-31G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let rhf = crate::scf::rhf(&system, &Default::default()).unwrap();
+        let options = GridOptions {
+            radial_points: 30,
+            angular_points: 110,
+            ..Default::default()
+        };
+        let grid = MolecularGrid::new(&system, &options).unwrap();
+
+        let density = DMatrix::from(&rhf.density);
+        let n = density.nrows();
+        let perturbation = DMatrix::from_fn(n, n, |i, j| ((i + j) as f64).sin() * 1e-2);
+        let perturbation = &perturbation + perturbation.transpose();
+        for functional in [Functional::Svwn5, Functional::Pbe, Functional::B3lyp] {
+            let (_, potential) =
+                super::exchange_correlation(&system, &grid, functional, &rhf.density);
+            let energy = |step: f64| {
+                let density = SymmetricMatrix::from(&(&density + step * &perturbation));
+                super::exchange_correlation(&system, &grid, functional, &density).0
+            };
+            let h = 1e-4;
+            let expected = (energy(h) - energy(-h)) / (2.0 * h);
+            approx::assert_relative_eq!(
+                DMatrix::from(&potential).dot(&perturbation),
+                expected,
+                max_relative = 1e-7
+            );
+        }
+    }
+}
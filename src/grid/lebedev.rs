@@ -0,0 +1,429 @@
+use std::f64::consts::PI;
+
+use nalgebra::Vector3;
+
+use Orbit::*;
+
+/// A set of points of a Lebedev grid that are equivalent under the symmetry operations of the
+/// octahedron, along with their common weight (normalized to 1 for the whole sphere).
+#[derive(Copy, Clone, Debug)]
+enum Orbit {
+    /// The 6 points (±1, 0, 0)
+    A1(f64),
+    /// The 12 points (0, ±1, ±1)/√2
+    A2(f64),
+    /// The 8 points (±1, ±1, ±1)/√3
+    A3(f64),
+    /// The 24 points (±l, ±l, ±m) with m = √(1 - 2l^2), given by l
+    B(f64, f64),
+    /// The 24 points (±p, ±q, 0) with q = √(1 - p^2), given by p
+    C(f64, f64),
+    /// The 48 points (±r, ±s, ±t) with t = √(1 - r^2 - s^2), given by r and s
+    D(f64, f64, f64),
+}
+
+impl Orbit {
+    /// Returns the weight and the generating point of this orbit.
+    fn generator(&self) -> (f64, [f64; 3]) {
+        match *self {
+            A1(weight) => (weight, [1.0, 0.0, 0.0]),
+            A2(weight) => (weight, [0.0, 0.5f64.sqrt(), 0.5f64.sqrt()]),
+            A3(weight) => (weight, [(1.0 / 3.0f64).sqrt(); 3]),
+            B(weight, l) => (weight, [l, l, (1.0 - 2.0 * l * l).sqrt()]),
+            C(weight, p) => (weight, [p, (1.0 - p * p).sqrt(), 0.0]),
+            D(weight, r, s) => (weight, [r, s, (1.0 - r * r - s * s).sqrt()]),
+        }
+    }
+
+    /// Returns all distinct points that follow from permutations and sign changes of the
+    /// generating point.
+    fn points(&self) -> Vec<Vector3<f64>> {
+        const PERMUTATIONS: [[usize; 3]; 6] = [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ];
+
+        let (_, generator) = self.generator();
+        let mut points = Vec::new();
+        for permutation in PERMUTATIONS {
+            for signs in 0..8 {
+                let point = Vector3::from_fn(|i, _| {
+                    let sign = if signs & (1 << i) == 0 { 1.0 } else { -1.0 };
+                    sign * generator[permutation[i]]
+                });
+                if !points.contains(&point) {
+                    points.push(point);
+                }
+            }
+        }
+        points
+    }
+}
+
+/// Returns the numbers of points of the available Lebedev grids in ascending order.
+pub fn lebedev_sizes() -> impl Iterator<Item = usize> {
+    LEBEDEV_GRIDS.iter().map(|&(n_points, _)| n_points)
+}
+
+/// Returns the points (unit vectors) and weights of the Lebedev grid with the given number of
+/// points, if it is available. The weights sum to 4π, so that the grid integrates functions over
+/// the unit sphere.
+pub(super) fn lebedev_grid(n_points: usize) -> Option<(Vec<Vector3<f64>>, Vec<f64>)> {
+    let (_, orbits) = LEBEDEV_GRIDS.iter().find(|&&(n, _)| n == n_points)?;
+    Some(
+        orbits
+            .iter()
+            .flat_map(|orbit| {
+                let (weight, _) = orbit.generator();
+                orbit
+                    .points()
+                    .into_iter()
+                    .map(move |point| (point, 4.0 * PI * weight))
+            })
+            .unzip(),
+    )
+}
+
+/// The orbits of the Lebedev grids by number of points. The parameters follow from the conditions
+/// that each grid integrates all spherical harmonics up to its degree exactly, for the orbits
+/// given by Lebedev and Laikov.
+///
+/// # References
+///
+/// [1] Lebedev, V. I.; Laikov, D. N. A quadrature formula for the sphere of the 131st algebraic
+/// order of accuracy. Dokl. Math. 1999, 59, 477–481
+const LEBEDEV_GRIDS: &[(usize, &[Orbit])] = &[
+    // degree 3
+    (6, &[A1(0.16666666666666666)]),
+    // degree 5
+    (14, &[A1(0.06666666666666667), A3(0.075)]),
+    // degree 7
+    (
+        26,
+        &[
+            A1(0.047619047619047616),
+            A2(0.0380952380952381),
+            A3(0.03214285714285714),
+        ],
+    ),
+    // degree 9
+    (
+        38,
+        &[
+            A1(0.009523809523809525),
+            A3(0.03214285714285714),
+            C(0.02857142857142857, 0.8880738339771153),
+        ],
+    ),
+    // degree 11
+    (
+        50,
+        &[
+            A1(0.012698412698412698),
+            A2(0.022574955908289243),
+            A3(0.02109375),
+            B(0.02017333553791887, 0.30151134457776363),
+        ],
+    ),
+    // degree 13
+    (
+        74,
+        &[
+            A1(0.0005130671797338464),
+            A2(0.01660406956574204),
+            A3(-0.029586038961038962),
+            B(0.026576207082159464, 0.4803844614152614),
+            C(0.01652217099371571, 0.9471562213625879),
+        ],
+    ),
+    // degree 15
+    (
+        86,
+        &[
+            A1(0.011544011544011544),
+            A3(0.011943909085856283),
+            B(0.011110555710603402, 0.36960284645415026),
+            B(0.011876501294537142, 0.6943540066026663),
+            C(0.011812303746904475, 0.9273306571511725),
+        ],
+    ),
+    // degree 17
+    (
+        110,
+        &[
+            A1(0.0038282704949371615),
+            A3(0.009793737512487513),
+            B(0.009942814891178103, 0.6904210483822921),
+            B(0.009595471336070962, 0.39568947305594193),
+            B(0.008211737283191111, 0.18511563534473616),
+            C(0.009694996361663029, 0.8781589106040661),
+        ],
+    ),
+    // degree 19
+    (
+        146,
+        &[
+            A1(0.0005996313688621381),
+            A2(0.0073729997186207565),
+            A3(0.007210515360144488),
+            B(0.006753829486314478, 0.41749612279654535),
+            B(0.007574394159054034, 0.15746766720390817),
+            B(0.007116355493117555, 0.6764410400114264),
+            D(
+                0.0069910873533032625,
+                0.8822700112603227,
+                0.44933283232695576,
+            ),
+        ],
+    ),
+    // degree 21
+    (
+        170,
+        &[
+            A1(0.005570590570193625),
+            A2(0.00462090535808734),
+            A3(-0.05699559846729051),
+            B(0.00630403463764219, 0.3668867215138179),
+            B(0.025447255859611943, 0.6070956562324068),
+            B(0.006173897540122068, 0.18679810866549065),
+            C(0.0065993885824074125, 0.9166669522280316),
+            D(0.006218761273860574, 0.7950856577369971, 0.5732538857053379),
+        ],
+    ),
+    // degree 23
+    (
+        194,
+        &[
+            A1(0.0017823404472446112),
+            A2(0.005716905949977102),
+            A3(0.005573383178848738),
+            B(0.005608704082587997, 0.6712973442695226),
+            B(0.004106777028169394, 0.12993354476500668),
+            B(0.0055187714672736135, 0.4446933178717437),
+            B(0.005158237711805383, 0.28924656275754385),
+            C(0.005051846064614809, 0.9383192181375916),
+            D(0.0055302489162330935, 0.8360360154824589, 0.525118572443642),
+        ],
+    ),
+    // degree 25
+    (
+        230,
+        &[
+            A1(-0.05522639919727325),
+            A3(0.004450274607445227),
+            B(0.004496841067921404, 0.44920446873976105),
+            B(0.00504915345047875, 0.25204194902102006),
+            B(0.003976408018051883, 0.6981906658447241),
+            B(0.0044014006503810136, 0.658740524346096),
+            B(0.017245443505444012, 0.040385440500976595),
+            C(0.004231083095357343, 0.8129136531733653),
+            C(0.005198069864064399, 0.9350227458805929),
+            D(0.004695720972568883, 0.8436365210688943, 0.4864661535886647),
+        ],
+    ),
+    // degree 27
+    (
+        266,
+        &[
+            A1(-0.0013137691273269524),
+            A2(-0.002522728704859336),
+            A3(0.004186853881700583),
+            B(0.005315167977810885, 0.7039373391585475),
+            B(0.004047142377086219, 0.10125262485724136),
+            B(0.00411248239440699, 0.4647448726420539),
+            B(0.0035955848997587824, 0.3277420654971629),
+            B(0.004256131351428158, 0.6620338663699974),
+            C(0.00422958270064724, 0.8506508083520399),
+            D(
+                0.004080914225780505,
+                0.9392279297499156,
+                0.32334845426928993,
+            ),
+            D(0.004071467593830964, 0.8193433888191203, 0.5244939240922365),
+        ],
+    ),
+    // degree 29
+    (
+        302,
+        &[
+            A1(0.0008545911725128148),
+            A3(0.0035991192850255713),
+            B(0.0034497884243058835, 0.3515640345570105),
+            B(0.003604822601419882, 0.6566329410219611),
+            B(0.003576729661743367, 0.4729054132581005),
+            B(0.002352101413689164, 0.09618308522614784),
+            B(0.0031089531224136754, 0.22196452362941785),
+            B(0.0036500458076772556, 0.7011766416089544),
+            C(0.0029823449631718037, 0.964408914879206),
+            C(0.00360082093221646, 0.8203264198277593),
+            D(0.003571540554273387, 0.8000727494073951, 0.5448677372580774),
+            D(
+                0.0033923122050061702,
+                0.9024425295330004,
+                0.4127724083168531,
+            ),
+        ],
+    ),
+    // degree 31
+    (
+        350,
+        &[
+            A1(0.0030067967494539364),
+            A3(0.0030506277456507714),
+            B(0.001621104600288991, 0.7068965463912317),
+            B(0.0030057014849017515, 0.4794682625712025),
+            B(0.002990992529653774, 0.1927533154878019),
+            B(0.0029821706441075955, 0.6930357961327123),
+            B(0.002721564237310992, 0.3608302115520091),
+            B(0.0030335137958111408, 0.6498486161496169),
+            C(0.0030079495552185333, 0.9811407828432572),
+            C(0.0028819646030553065, 0.924966152698679),
+            D(0.0029583576265356957, 0.7934537856582317, 0.535123047718276),
+            D(
+                0.0030360200264070883,
+                0.8280801506686862,
+                0.5521820743493993,
+            ),
+            D(
+                0.0028321874039263033,
+                0.9074658265305128,
+                0.37800918987448656,
+            ),
+        ],
+    ),
+    // degree 35
+    (
+        434,
+        &[
+            A1(0.0005265897968224436),
+            A2(0.0025482199720026073),
+            A3(0.0025123174189273074),
+            B(0.002530403801186355, 0.6909346307509111),
+            B(0.002014279020918528, 0.1774836054609158),
+            B(0.0025017251684029363, 0.49143426377847466),
+            B(0.0025132671745975643, 0.6456664707424257),
+            B(0.0023026947822274157, 0.2861289010307638),
+            B(0.0014624956215946137, 0.07568084367178018),
+            B(0.00244537343731298, 0.39272597633680023),
+            C(0.002417442375638981, 0.8818132877794288),
+            C(0.0019109512821795323, 0.9776428111182649),
+            D(
+                0.0024169300443247755,
+                0.8689460322872412,
+                0.45023303825826255,
+            ),
+            D(0.002512236854563495, 0.7999278543857286, 0.5905157048925271),
+            D(0.002496644054553086, 0.7717462626915901, 0.5550152361076807),
+            D(
+                0.002236607760437849,
+                0.9371809858553722,
+                0.33443631453434547,
+            ),
+        ],
+    ),
+    // degree 41
+    (
+        590,
+        &[
+            A1(0.0003095121295306187),
+            A3(0.001852379698597489),
+            B(0.0018717906392777437, 0.7040954938227469),
+            B(0.001858812585438317, 0.6807744066455242),
+            B(0.001852028828296213, 0.6372546939258752),
+            B(0.0018467159561512418, 0.5044419707800358),
+            B(0.0018184717781627687, 0.4215761784010967),
+            B(0.001749564657281154, 0.3317920736472123),
+            B(0.001617210647254411, 0.23847367014218873),
+            B(0.001384737234851692, 0.14590364491577631),
+            B(0.000976433116505105, 0.06095034115507196),
+            C(0.001857161196774078, 0.791101929626902),
+            C(0.001705153996395864, 0.918045287711454),
+            C(0.0013003216858860477, 0.9850133350280019),
+            D(0.0018428664729052855, 0.749310611904116, 0.561026380862206),
+            D(0.0018026589343774512, 0.8400474883590504, 0.474239284255198),
+            D(
+                0.0018498305604436602,
+                0.7803207424799203,
+                0.5984126497885379,
+            ),
+            D(
+                0.0017139045071067087,
+                0.9092134750923736,
+                0.3791035407695563,
+            ),
+            D(
+                0.0015552136033968086,
+                0.9571020743100725,
+                0.27786731905862444,
+            ),
+            D(
+                0.0018022391280085254,
+                0.8593798558907212,
+                0.5033564271075117,
+            ),
+        ],
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::LEBEDEV_GRIDS;
+
+    /// Returns (2n - 1)!!
+    fn double_factorial(n: i32) -> f64 {
+        (1..=n).map(|i| (2 * i - 1) as f64).product()
+    }
+
+    #[test]
+    fn exact_integrals() {
+        let degrees = [
+            3, 5, 7, 9, 11, 13, 15, 17, 19, 21, 23, 25, 27, 29, 31, 35, 41,
+        ];
+        for (&(n_points, _), degree) in LEBEDEV_GRIDS.iter().zip(degrees) {
+            let (points, weights) = super::lebedev_grid(n_points).unwrap();
+            assert_eq!(points.len(), n_points);
+            for point in &points {
+                approx::assert_relative_eq!(point.norm(), 1.0, epsilon = 1e-15);
+            }
+
+            // ∫ x^2i y^2j z^2k dΩ = 4π (2i - 1)!! (2j - 1)!! (2k - 1)!! / (2(i + j + k) + 1)!!
+            for i in 0..=degree / 2 {
+                for j in 0..=degree / 2 - i {
+                    for k in 0..=degree / 2 - i - j {
+                        let integral: f64 = points
+                            .iter()
+                            .zip(&weights)
+                            .map(|(point, weight)| {
+                                weight
+                                    * point.x.powi(2 * i)
+                                    * point.y.powi(2 * j)
+                                    * point.z.powi(2 * k)
+                            })
+                            .sum();
+                        let expected = 4.0
+                            * PI
+                            * double_factorial(i)
+                            * double_factorial(j)
+                            * double_factorial(k)
+                            / double_factorial(i + j + k + 1);
+                        approx::assert_abs_diff_eq!(integral, expected, epsilon = 1e-13);
+                    }
+                }
+            }
+
+            // odd powers vanish by symmetry
+            let odd: f64 = points
+                .iter()
+                .zip(&weights)
+                .map(|(point, weight)| weight * point.x * point.y.powi(2) * point.z.powi(3))
+                .sum();
+            approx::assert_abs_diff_eq!(odd, 0.0, epsilon = 1e-15);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! This module contains atom-centered molecular integration grids, as used for the numerical
+//! integration of densities and exchange-correlation functionals.
+
+mod lebedev;
+mod partition;
+mod radial;
+
+use anyhow::Context;
+use itertools::Itertools;
+use nalgebra::Point3;
+
+use crate::{periodic_table::ElementType, system::MolecularSystem};
+
+pub use lebedev::lebedev_sizes;
+pub use partition::Partitioning;
+pub use radial::RadialScheme;
+
+/// Points with a smaller (absolute) weight are dropped from the grid
+const WEIGHT_CUTOFF: f64 = 1e-15;
+
+/// Options that control the construction of a [MolecularGrid].
+#[derive(Clone, Debug)]
+pub struct GridOptions {
+    pub radial_scheme: RadialScheme,
+    /// The number of radial points of hydrogen and helium, which grows by 5 for every further
+    /// period
+    pub radial_points: usize,
+    /// The number of points of the Lebedev grid, which must be one of [lebedev_sizes]
+    pub angular_points: usize,
+    pub partitioning: Partitioning,
+    /// Whether smaller angular grids are used close to the nuclei, where the density is almost
+    /// spherical, based on the Bragg-Slater radius of each element
+    pub pruning: bool,
+}
+
+impl Default for GridOptions {
+    fn default() -> Self {
+        Self {
+            radial_scheme: RadialScheme::TreutlerAhlrichs,
+            radial_points: 50,
+            angular_points: 302,
+            partitioning: Partitioning::Becke,
+            pruning: true,
+        }
+    }
+}
+
+/// A molecular integration grid, i.e., the union of the atomic grids of all atoms with basis
+/// functions with weights that include the partitioning among the atoms.
+#[derive(Clone, Debug)]
+pub struct MolecularGrid {
+    pub points: Vec<Point3<f64>>,
+    pub weights: Vec<f64>,
+}
+
+impl MolecularGrid {
+    /// Builds the grid for the atoms with basis functions of the given [MolecularSystem], which
+    /// fails if the number of angular points is not available.
+    pub fn new(system: &MolecularSystem, options: &GridOptions) -> anyhow::Result<Self> {
+        // the full angular grid and the smaller ones of pruned regions
+        let angular_grids = PRUNED_SIZES
+            .iter()
+            .copied()
+            .filter(|&size| size < options.angular_points)
+            .chain([options.angular_points])
+            .map(|size| {
+                let grid = lebedev::lebedev_grid(size).with_context(|| {
+                    format!(
+                        "no Lebedev grid with {size} points, available are {}",
+                        lebedev_sizes().join(", ")
+                    )
+                })?;
+                Ok((size, grid))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let atoms = system
+            .atoms
+            .iter()
+            .filter(|atom| atom.has_basis())
+            .map(|atom| {
+                let element = ElementType::from_ordinal(atom.ordinal)
+                    .with_context(|| format!("unknown atom ordinal {}", atom.ordinal))?;
+                Ok((element, atom.position))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let centers = atoms.iter().map(|&(_, center)| center).collect_vec();
+        let radii = atoms
+            .iter()
+            .map(|(element, _)| element.bragg_slater_radius())
+            .collect_vec();
+        let partition = partition::Partition::new(options.partitioning, &centers, &radii);
+
+        let mut grid = MolecularGrid {
+            points: Vec::new(),
+            weights: Vec::new(),
+        };
+        for (atom, &(element, center)) in atoms.iter().enumerate() {
+            let n_radial = options.radial_points + 5 * (element.period() - 1);
+            let (radii, radial_weights) = options.radial_scheme.points(element, n_radial);
+
+            for (&r, &radial_weight) in radii.iter().zip(&radial_weights) {
+                let size = angular_size(element, r, options);
+                let (_, (directions, weights)) = angular_grids
+                    .iter()
+                    .find(|&&(n_points, _)| n_points == size)
+                    .unwrap();
+
+                for (direction, angular_weight) in directions.iter().zip(weights) {
+                    let point = center + r * direction;
+                    let weight = radial_weight * angular_weight * partition.weight(atom, &point);
+                    if weight.abs() > WEIGHT_CUTOFF {
+                        grid.points.push(point);
+                        grid.weights.push(weight);
+                    }
+                }
+            }
+        }
+        Ok(grid)
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the integral of a function with the given values at the points of this grid.
+    pub fn integrate(&self, values: &[f64]) -> f64 {
+        self.weights.iter().zip(values).map(|(w, v)| w * v).sum()
+    }
+}
+
+/// The numbers of angular points of the inner regions of pruned grids, from the nucleus outwards,
+/// which are limited by the number of points of the full grid
+const PRUNED_SIZES: [usize; 2] = [50, 110];
+
+/// Returns the number of angular points at the given distance from an atom of the given element.
+/// The inner regions of pruned grids are bounded by fractions of the Bragg-Slater radius that
+/// depend on the period, similar to the scheme of NWChem.
+fn angular_size(element: ElementType, r: f64, options: &GridOptions) -> usize {
+    if !options.pruning {
+        return options.angular_points;
+    }
+    let fractions = match element.period() {
+        1 => [0.25, 0.5],
+        2 => [0.1667, 0.5],
+        _ => [0.1, 0.4],
+    };
+    fractions
+        .iter()
+        .zip(PRUNED_SIZES)
+        .find(|&(fraction, _)| r < fraction * element.bragg_slater_radius())
+        .map_or(options.angular_points, |(_, size)| {
+            size.min(options.angular_points)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{basis::BasisSet, system::MolecularSystem};
+
+    use super::{GridOptions, MolecularGrid, Partitioning, RadialScheme};
+
+    #[test]
+    fn electron_count() {
+        let basis_set = BasisSet::load("data/basis/6-31G_st.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let rhf = crate::scf::rhf(&system, &Default::default()).unwrap();
+
+        for (radial_scheme, partitioning) in [
+            (RadialScheme::TreutlerAhlrichs, Partitioning::Becke),
+            (RadialScheme::MuraKnowles, Partitioning::Stratmann),
+        ] {
+            let options = GridOptions {
+                radial_scheme,
+                radial_points: 75,
+                angular_points: 434,
+                partitioning,
+                pruning: true,
+            };
+            let grid = MolecularGrid::new(&system, &options).unwrap();
+            let density = crate::electron_density(&system, &rhf.density, &grid.points);
+            approx::assert_abs_diff_eq!(grid.integrate(&density), 10.0, epsilon = 1e-6);
+        }
+
+        let options = GridOptions {
+            angular_points: 300,
+            ..Default::default()
+        };
+        assert!(MolecularGrid::new(&system, &options).is_err());
+    }
+}
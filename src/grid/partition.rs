@@ -0,0 +1,131 @@
+use nalgebra::{DMatrix, Point3};
+
+/// How the integrand is divided among the atoms, i.e., the weight functions that make the atomic
+/// grids overlap smoothly.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Partitioning {
+    /// The fuzzy cells of Becke with three iterations of p(μ) = 3/2 μ - 1/2 μ^3 and atomic size
+    /// adjustments based on Bragg-Slater radii.
+    ///
+    /// # References
+    ///
+    /// [1] Becke, A. D. A multicenter numerical integration scheme for polyatomic molecules. J.
+    /// Chem. Phys. 1988, 88, 2547–2553
+    Becke,
+    /// The piecewise polynomial cell functions of Stratmann, Scuseria and Frisch, which vanish
+    /// exactly close to other atoms. Points close to their own atom get the weight 1 directly.
+    ///
+    /// # References
+    ///
+    /// [1] Stratmann, R. E.; Scuseria, G. E.; Frisch, M. J. Achieving linear scaling in
+    /// exchange-correlation density functional quadratures. Chem. Phys. Lett. 1996, 257, 213–223
+    Stratmann,
+}
+
+/// The width of the switching region of the cell functions of Stratmann et al.
+const STRATMANN_WIDTH: f64 = 0.64;
+
+/// Computes the partition weights of points of the atomic grids for a set of atoms.
+pub(super) struct Partition {
+    partitioning: Partitioning,
+    centers: Vec<Point3<f64>>,
+    /// The inverse distances between the atoms
+    inverse_distances: DMatrix<f64>,
+    /// The atomic size adjustments a_ij of Becke's scheme
+    adjustments: DMatrix<f64>,
+    /// Points closer to their atom than this have the weight 1 in Stratmann's scheme
+    inner_radii: Vec<f64>,
+}
+
+impl Partition {
+    /// Creates a [Partition] for atoms at the given centers with the given (Bragg-Slater) radii.
+    pub(super) fn new(partitioning: Partitioning, centers: &[Point3<f64>], radii: &[f64]) -> Self {
+        let n = centers.len();
+        let distances = DMatrix::from_fn(n, n, |i, j| nalgebra::distance(&centers[i], &centers[j]));
+        let inverse_distances =
+            distances.map(|distance| if distance > 0.0 { 1.0 / distance } else { 0.0 });
+        let adjustments = DMatrix::from_fn(n, n, |i, j| {
+            let chi = radii[i] / radii[j];
+            let u = (chi - 1.0) / (chi + 1.0);
+            if u == 0.0 {
+                0.0
+            } else {
+                (u / (u * u - 1.0)).clamp(-0.5, 0.5)
+            }
+        });
+        let inner_radii = (0..n)
+            .map(|i| {
+                let nearest = (0..n)
+                    .filter(|&j| j != i)
+                    .map(|j| distances[(i, j)])
+                    .fold(f64::INFINITY, f64::min);
+                0.5 * (1.0 - STRATMANN_WIDTH) * nearest
+            })
+            .collect();
+
+        Partition {
+            partitioning,
+            centers: centers.to_vec(),
+            inverse_distances,
+            adjustments,
+            inner_radii,
+        }
+    }
+
+    /// Returns the cell function s(μ_ij) of atom i with respect to atom j.
+    fn cell_function(&self, i: usize, j: usize, mu: f64) -> f64 {
+        match self.partitioning {
+            Partitioning::Becke => {
+                let mut nu = mu + self.adjustments[(i, j)] * (1.0 - mu * mu);
+                for _ in 0..3 {
+                    nu = 1.5 * nu - 0.5 * nu.powi(3);
+                }
+                0.5 * (1.0 - nu)
+            }
+            Partitioning::Stratmann => {
+                if mu <= -STRATMANN_WIDTH {
+                    1.0
+                } else if mu >= STRATMANN_WIDTH {
+                    0.0
+                } else {
+                    let x = mu / STRATMANN_WIDTH;
+                    let x2 = x * x;
+                    let g = x * (35.0 + x2 * (-35.0 + x2 * (21.0 - 5.0 * x2))) / 16.0;
+                    0.5 * (1.0 - g)
+                }
+            }
+        }
+    }
+
+    /// Returns the weight of the given point of the grid of the given atom.
+    pub(super) fn weight(&self, atom: usize, point: &Point3<f64>) -> f64 {
+        let distances: Vec<_> = self
+            .centers
+            .iter()
+            .map(|center| nalgebra::distance(center, point))
+            .collect();
+        if self.partitioning == Partitioning::Stratmann && distances[atom] < self.inner_radii[atom]
+        {
+            return 1.0;
+        }
+
+        let n = self.centers.len();
+        let cell = |i: usize| -> f64 {
+            let mut product = 1.0;
+            for j in (0..n).filter(|&j| j != i) {
+                let mu = (distances[i] - distances[j]) * self.inverse_distances[(i, j)];
+                product *= self.cell_function(i, j, mu);
+                if product == 0.0 {
+                    break;
+                }
+            }
+            product
+        };
+
+        let own = cell(atom);
+        if own == 0.0 {
+            return 0.0;
+        }
+        own / (0..n).map(cell).sum::<f64>()
+    }
+}
@@ -0,0 +1,108 @@
+use std::f64::consts::{LN_2, PI};
+
+use crate::periodic_table::ElementType;
+
+/// The radial quadrature of the atomic grids, which maps a quadrature on a finite interval to
+/// [0, ∞).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RadialScheme {
+    /// The M4 mapping r = ξ/ln(2) (1 + x)^0.6 ln(2/(1 - x)) of a Chebyshev quadrature of the
+    /// second kind, with element-specific scaling factors ξ.
+    ///
+    /// # References
+    ///
+    /// [1] Treutler, O.; Ahlrichs, R. Efficient molecular numerical integration schemes. J. Chem.
+    /// Phys. 1995, 102, 346–354
+    TreutlerAhlrichs,
+    /// The mapping r = -α ln(1 - x^3) of the midpoint rule on (0, 1), with α = 7 for the more
+    /// diffuse alkali and alkaline earth metals and α = 5 otherwise.
+    ///
+    /// # References
+    ///
+    /// [1] Mura, M. E.; Knowles, P. J. Improved radial grids for quadrature in molecular
+    /// density-functional calculations. J. Chem. Phys. 1996, 104, 9848–9858
+    MuraKnowles,
+}
+
+impl RadialScheme {
+    /// Returns the given number of radial points for the given element with their weights, which
+    /// include the volume element r^2.
+    pub(super) fn points(&self, element: ElementType, n_points: usize) -> (Vec<f64>, Vec<f64>) {
+        match self {
+            RadialScheme::TreutlerAhlrichs => {
+                let scale = element.treutler_ahlrichs_scale() / LN_2;
+                (1..=n_points)
+                    .map(|i| {
+                        let angle = i as f64 * PI / (n_points + 1) as f64;
+                        let x = angle.cos();
+                        let log = (2.0 / (1.0 - x)).ln();
+                        let r = scale * (1.0 + x).powf(0.6) * log;
+                        let derivative = scale
+                            * (0.6 * (1.0 + x).powf(-0.4) * log + (1.0 + x).powf(0.6) / (1.0 - x));
+                        // the weight of the Chebyshev quadrature for ∫ f(x) dx
+                        let weight = PI / (n_points + 1) as f64 * angle.sin();
+                        (r, weight * derivative * r * r)
+                    })
+                    .unzip()
+            }
+            RadialScheme::MuraKnowles => {
+                let alpha = match element as usize {
+                    3 | 4 | 11 | 12 | 19 | 20 | 37 | 38 | 55 | 56 | 87 | 88 => 7.0,
+                    _ => 5.0,
+                };
+                (0..n_points)
+                    .map(|i| {
+                        let x = (i as f64 + 0.5) / n_points as f64;
+                        let r = -alpha * (1.0 - x.powi(3)).ln();
+                        let derivative = 3.0 * alpha * x * x / (1.0 - x.powi(3));
+                        (r, derivative * r * r / n_points as f64)
+                    })
+                    .unzip()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::periodic_table::ElementType;
+
+    use super::RadialScheme;
+
+    /// Returns the integral ∫ r^2 exp(-a r^2) dr with the given radial points and weights, relative
+    /// to its exact value sqrt(π) / (4 a^(3/2)).
+    fn relative_gaussian_integral(points: &[f64], weights: &[f64], exponent: f64) -> f64 {
+        let integral: f64 = points
+            .iter()
+            .zip(weights)
+            .map(|(r, weight)| weight * (-exponent * r * r).exp())
+            .sum();
+        integral / (std::f64::consts::PI.sqrt() / (4.0 * f64::powf(exponent, 1.5)))
+    }
+
+    #[test]
+    fn gaussian_integrals() {
+        for scheme in [RadialScheme::TreutlerAhlrichs, RadialScheme::MuraKnowles] {
+            for element in [ElementType::H, ElementType::Li, ElementType::Cl] {
+                let (points, weights) = scheme.points(element, 75);
+                for exponent in [0.1, 1.0, 30.0, 2000.0] {
+                    let integral = relative_gaussian_integral(&points, &weights, exponent);
+                    approx::assert_relative_eq!(integral, 1.0, max_relative = 1e-7);
+                }
+            }
+        }
+
+        // with few points, the larger scale of the metals is needed for their diffuse functions,
+        // while the smaller scale of the other elements is needed for tight core functions
+        for (element, exponents, tolerance) in [
+            (ElementType::Li, [0.05, 1.0, 10.0], 1e-4),
+            (ElementType::O, [0.3, 100.0, 3000.0], 1e-3),
+        ] {
+            let (points, weights) = RadialScheme::MuraKnowles.points(element, 30);
+            for exponent in exponents {
+                let integral = relative_gaussian_integral(&points, &weights, exponent);
+                approx::assert_relative_eq!(integral, 1.0, max_relative = tolerance);
+            }
+        }
+    }
+}
@@ -0,0 +1,693 @@
+//! Integrals over the semi-local effective core potentials of [Ecp].
+//!
+//! The angular parts are integrated analytically by expanding the Gaussians around the center of
+//! the potential, relative to which all positions are given here, in spherical harmonics:
+//!     exp(-α|r - A|^2) = exp(-α (r - |A|)^2) 4π Σ_λ ĩ_λ(2α|A|r) Σ_μ Y_λμ(Â) Y_λμ(r̂)
+//! where ĩ_λ(x) = exp(-x) i_λ(x) are scaled modified spherical Bessel functions. The remaining
+//! radial integrals are evaluated by Gauss-Legendre quadrature around the maximum of the integrand.
+//!
+//! # References
+//!
+//! [1] McMurchie, L. E.; Davidson, E. R. Calculation of integrals over ab initio pseudopotentials.
+//!     J. Comput. Phys. 1981, 44, 289–301
+
+use std::f64::consts::PI;
+
+use itertools::Itertools;
+use nalgebra::{DMatrix, Point3, Vector3};
+use ndarray::{Array3, Array4, Axis};
+
+use crate::{
+    basis::Ecp,
+    system::{MolecularSystem, ShellBasis},
+};
+
+use super::utils::gauss_legendre;
+
+/// The number of quadrature points of each radial integral
+const RADIAL_POINTS: usize = 48;
+/// Radial integrals whose integrand stays below exp(-SCREENING_EXPONENT) are skipped
+const SCREENING_EXPONENT: f64 = 60.0;
+
+/// Evaluates the integrals over all effective core potentials of a [MolecularSystem].
+pub(super) struct EcpIntegrals<'a> {
+    centers: Vec<EcpCenter<'a>>,
+    /// ∫ Y_λμ(r̂) x̂^i ŷ^j ẑ^k dΩ, indexed by [λ^2 + λ + μ, i, j, k]
+    angular_integrals: Array4<f64>,
+    radial_nodes: (Vec<f64>, Vec<f64>),
+}
+
+/// The center of an effective core potential along with the projections of all basis functions of
+/// the system onto its semi-local potentials.
+struct EcpCenter<'a> {
+    position: Point3<f64>,
+    ecp: &'a Ecp,
+    /// The angular integrals of each cartesian basis function with each semi-local potential,
+    /// see [angular_projection]
+    projections: Vec<Vec<Array3<f64>>>,
+}
+
+impl<'a> EcpIntegrals<'a> {
+    /// Prepares the integrals over the effective core potentials of all atoms of the system.
+    pub(super) fn new(system: &'a MolecularSystem) -> Self {
+        let centers = system
+            .atoms
+            .iter()
+            .enumerate()
+            .filter_map(|(atom_index, atom)| Some((atom.position, system.ecp(atom_index)?)))
+            .collect_vec();
+        Self::with_centers(system, &centers)
+    }
+
+    /// Prepares the integrals over the given effective core potentials, which may be placed
+    /// anywhere.
+    fn with_centers(system: &MolecularSystem, centers: &[(Point3<f64>, &'a Ecp)]) -> Self {
+        let max_basis = (0..system.n_shells())
+            .map(|shell| system.shell_basis(shell).shell_type.0)
+            .max()
+            .unwrap_or(0);
+        let max_ecp = centers
+            .iter()
+            .map(|(_, ecp)| ecp.max_angular_magnitude())
+            .max()
+            .unwrap_or(0)
+            .max(0);
+
+        // the angular integrands are polynomials of at most this degree
+        let grid = AngularGrid::new(2 * (max_ecp + max_basis).max(2 * max_basis) as usize);
+        let harmonics = grid
+            .points
+            .iter()
+            .map(|point| spherical_harmonics(max_ecp.max(2 * max_basis), point))
+            .collect_vec();
+
+        let l_max = 2 * max_basis as usize;
+        let mut angular_integrals =
+            Array4::zeros([(l_max + 1).pow(2), l_max + 1, l_max + 1, l_max + 1]);
+        for ((point, weight), harmonics) in grid.points.iter().zip(&grid.weights).zip(&harmonics) {
+            for (i, j, k) in itertools::iproduct!(0..=l_max, 0..=l_max, 0..=l_max) {
+                if i + j + k > l_max {
+                    continue;
+                }
+                let monomial = weight
+                    * point.x.powi(i as i32)
+                    * point.y.powi(j as i32)
+                    * point.z.powi(k as i32);
+                for (index, harmonic) in harmonics[..(l_max + 1).pow(2)].iter().enumerate() {
+                    angular_integrals[[index, i, j, k]] += harmonic * monomial;
+                }
+            }
+        }
+
+        let centers = centers
+            .iter()
+            .map(|&(position, ecp)| {
+                let projections = (0..system.n_shells())
+                    .flat_map(|shell| {
+                        let shell = system.shell_basis(shell);
+                        let shift = shell.center - position;
+                        shell
+                            .basis
+                            .iter()
+                            .map(move |function| (function.angular, shift))
+                    })
+                    .map(|(angular, shift)| {
+                        ecp.semi_local
+                            .iter()
+                            .map(|&(l, _)| angular_projection(l, angular, shift, &grid, &harmonics))
+                            .collect()
+                    })
+                    .collect();
+                EcpCenter {
+                    position,
+                    ecp,
+                    projections,
+                }
+            })
+            .collect();
+
+        Self {
+            centers,
+            angular_integrals,
+            radial_nodes: gauss_legendre(RADIAL_POINTS),
+        }
+    }
+
+    /// Returns whether there are no effective core potentials, i.e., all integrals are zero.
+    pub(super) fn is_empty(&self) -> bool {
+        self.centers.is_empty()
+    }
+
+    /// Computes the integrals between the cartesian components of two shells.
+    pub(super) fn compute(&self, basis_a: ShellBasis, basis_b: ShellBasis) -> DMatrix<f64> {
+        let mut result = DMatrix::zeros(basis_a.count, basis_b.count);
+        for center in &self.centers {
+            self.add_local(center, &basis_a, &basis_b, &mut result);
+            self.add_semi_local(center, &basis_a, &basis_b, &mut result);
+        }
+        result
+    }
+
+    /// Adds the integrals over the local potential U_L of the given center, for which the product
+    /// of both Gaussians is expanded around the center at once.
+    fn add_local(
+        &self,
+        center: &EcpCenter,
+        basis_a: &ShellBasis,
+        basis_b: &ShellBasis,
+        result: &mut DMatrix<f64>,
+    ) {
+        let shift_a = basis_a.center - center.position;
+        let shift_b = basis_b.center - center.position;
+        let l_max = (basis_a.shell_type.0 + basis_b.shell_type.0) as usize;
+
+        // the angular integrals of the products of the polynomial parts of both functions with
+        // all Y_λμ, indexed by [a, b, λ^2 + λ + μ, degree]
+        let mut polynomial_integrals =
+            Array4::<f64>::zeros([basis_a.count, basis_b.count, (l_max + 1).pow(2), l_max + 1]);
+        for (a, function_a) in basis_a.basis.iter().enumerate() {
+            let polynomial_a = shifted_polynomial(function_a.angular, shift_a);
+            for (b, function_b) in basis_b.basis.iter().enumerate() {
+                let polynomial_b = shifted_polynomial(function_b.angular, shift_b);
+                for ((ia, ja, ka), coefficient_a) in polynomial_a.indexed_iter() {
+                    for ((ib, jb, kb), coefficient_b) in polynomial_b.indexed_iter() {
+                        let (i, j, k) = (ia + ib, ja + jb, ka + kb);
+                        let coefficient = coefficient_a * coefficient_b;
+                        for index in 0..(l_max + 1).pow(2) {
+                            polynomial_integrals[[a, b, index, i + j + k]] +=
+                                coefficient * self.angular_integrals[[index, i, j, k]];
+                        }
+                    }
+                }
+            }
+        }
+
+        let (distance_a, distance_b) = (shift_a.norm(), shift_b.norm());
+        for (index_a, &exponent_a) in basis_a.basis[0].exponents.iter().enumerate() {
+            for (index_b, &exponent_b) in basis_b.basis[0].exponents.iter().enumerate() {
+                let combined = exponent_a * shift_a + exponent_b * shift_b;
+                let combined_norm = combined.norm();
+                let harmonics = spherical_harmonics(l_max as i32, &direction(&combined));
+                let offset = exponent_a * distance_a.powi(2) + exponent_b * distance_b.powi(2);
+
+                // H[a, b, degree, λ] = 4π Σ_μ Y_λμ(q̂) ∫ Y_λμ(r̂) P_a(r̂) P_b(r̂) dΩ
+                let mut expansion =
+                    Array4::<f64>::zeros([basis_a.count, basis_b.count, l_max + 1, l_max + 1]);
+                for ((a, b, index, degree), value) in polynomial_integrals.indexed_iter() {
+                    let lambda = (index as f64).sqrt() as usize;
+                    expansion[[a, b, degree, lambda]] += 4.0 * PI * harmonics[index] * value;
+                }
+
+                for term in &center.ecp.local {
+                    let exponent = exponent_a + exponent_b + term.exponent;
+                    if combined_norm.powi(2) / exponent - offset < -SCREENING_EXPONENT {
+                        continue;
+                    }
+
+                    let power = (term.power + 2).max(0) as usize + 2 * l_max;
+                    for (r, weight) in self.radial_quadrature(combined_norm, exponent, power) {
+                        let radial = weight
+                            * term.value(r)
+                            * r
+                            * r
+                            * f64::exp(
+                                -(exponent_a + exponent_b) * r * r + 2.0 * combined_norm * r
+                                    - offset,
+                            );
+                        let bessel = scaled_bessel(l_max, 2.0 * combined_norm * r);
+
+                        for (a, b) in itertools::iproduct!(0..basis_a.count, 0..basis_b.count) {
+                            let mut sum = 0.0;
+                            for degree in 0..=l_max {
+                                let angular: f64 = (0..=l_max)
+                                    .map(|lambda| {
+                                        bessel[lambda] * expansion[[a, b, degree, lambda]]
+                                    })
+                                    .sum();
+                                sum += r.powi(degree as i32) * angular;
+                            }
+
+                            result[(a, b)] += basis_a.basis[a].coefficients[index_a]
+                                * basis_b.basis[b].coefficients[index_b]
+                                * radial
+                                * sum;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the integrals over the semi-local potentials U_l - U_L of the given center, for which
+    /// both functions are projected onto the Y_lm separately.
+    fn add_semi_local(
+        &self,
+        center: &EcpCenter,
+        basis_a: &ShellBasis,
+        basis_b: &ShellBasis,
+        result: &mut DMatrix<f64>,
+    ) {
+        let distance_a = (basis_a.center - center.position).norm();
+        let distance_b = (basis_b.center - center.position).norm();
+        let (l_a, l_b) = (basis_a.shell_type.0, basis_b.shell_type.0);
+
+        for (channel, (l, terms)) in center.ecp.semi_local.iter().enumerate() {
+            let n_m = 2 * *l as usize + 1;
+            let projections_a =
+                &center.projections[basis_a.start_index..basis_a.start_index + basis_a.count];
+            let projections_b =
+                &center.projections[basis_b.start_index..basis_b.start_index + basis_b.count];
+
+            for (index_a, &exponent_a) in basis_a.basis[0].exponents.iter().enumerate() {
+                for (index_b, &exponent_b) in basis_b.basis[0].exponents.iter().enumerate() {
+                    for term in terms {
+                        let exponent = exponent_a + exponent_b + term.exponent;
+                        let peak = exponent_a * distance_a + exponent_b * distance_b;
+                        let offset =
+                            exponent_a * distance_a.powi(2) + exponent_b * distance_b.powi(2);
+                        if peak.powi(2) / exponent - offset < -SCREENING_EXPONENT {
+                            continue;
+                        }
+
+                        let power = (term.power + 2).max(0) as usize + 2 * (l + l_a + l_b) as usize;
+                        for (r, weight) in self.radial_quadrature(peak, exponent, power) {
+                            let radial = weight
+                                * term.value(r)
+                                * r
+                                * r
+                                * f64::exp(
+                                    -exponent_a * (r - distance_a).powi(2)
+                                        - exponent_b * (r - distance_b).powi(2),
+                                );
+
+                            let project =
+                                |basis: &ShellBasis,
+                                 projections: &[Vec<Array3<f64>>],
+                                 index: usize,
+                                 exponent: f64,
+                                 distance: f64| {
+                                    let l_max = (l + basis.shell_type.0) as usize;
+                                    let bessel =
+                                        scaled_bessel(l_max, 2.0 * exponent * distance * r);
+                                    DMatrix::from_fn(basis.count, n_m, |function, m| {
+                                        let sum: f64 = projections[function][channel]
+                                            .index_axis(Axis(0), m)
+                                            .indexed_iter()
+                                            .map(|((degree, lambda), value)| {
+                                                r.powi(degree as i32) * bessel[lambda] * value
+                                            })
+                                            .sum();
+                                        basis.basis[function].coefficients[index] * sum
+                                    })
+                                };
+                            let a =
+                                project(basis_a, projections_a, index_a, exponent_a, distance_a);
+                            let b =
+                                project(basis_b, projections_b, index_b, exponent_b, distance_b);
+                            result.gemm(radial, &a, &b.transpose(), 1.0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the Gauss-Legendre points and weights for a radial integrand that behaves like
+    /// r^power exp(-exponent r^2 + 2 peak r), covering the region where it doesn't vanish.
+    fn radial_quadrature(
+        &self,
+        peak: f64,
+        exponent: f64,
+        power: usize,
+    ) -> impl Iterator<Item = (f64, f64)> + '_ {
+        let center = peak / exponent;
+        let half_width = (8.0 + (power as f64).sqrt()) / exponent.sqrt();
+        let start = (center - half_width).max(0.0);
+        let end = center + half_width;
+
+        let (nodes, weights) = &self.radial_nodes;
+        nodes.iter().zip(weights).map(move |(node, weight)| {
+            let r = start + 0.5 * (end - start) * (node + 1.0);
+            (r, 0.5 * (end - start) * weight)
+        })
+    }
+}
+
+/// Returns the angular integrals of a cartesian function with the given angular vector and center
+/// with the Y_lm of the given l, which yields the projection of the function onto Y_lm:
+///     ∫ Y_lm G dΩ = Σ_α c_α exp(-α (r - |A|)^2) Σ_{K, λ} r^K ĩ_λ(2α|A|r) P[m, K, λ]
+fn angular_projection(
+    l: i32,
+    angular: [i32; 3],
+    shift: Vector3<f64>,
+    grid: &AngularGrid,
+    harmonics: &[Vec<f64>],
+) -> Array3<f64> {
+    let degree = angular.iter().sum::<i32>() as usize;
+    let lambda_max = l as usize + degree;
+    let polynomial = shifted_polynomial(angular, shift);
+    let direction = direction(&shift);
+
+    let mut projection = Array3::zeros([2 * l as usize + 1, degree + 1, lambda_max + 1]);
+    for ((point, weight), harmonics) in grid.points.iter().zip(&grid.weights).zip(harmonics) {
+        // by the addition theorem, 4π Σ_μ Y_λμ(Â) Y_λμ(r̂) = (2λ + 1) P_λ(Â·r̂)
+        let legendre = legendre(lambda_max, direction.dot(point));
+        let mut polynomial_values = vec![0.0; degree + 1];
+        for ((i, j, k), coefficient) in polynomial.indexed_iter() {
+            polynomial_values[i + j + k] += coefficient
+                * point.x.powi(i as i32)
+                * point.y.powi(j as i32)
+                * point.z.powi(k as i32);
+        }
+
+        let harmonics = &harmonics[(l * l) as usize..((l + 1) * (l + 1)) as usize];
+        for ((m, degree, lambda), value) in projection.indexed_iter_mut() {
+            *value += weight
+                * harmonics[m]
+                * polynomial_values[degree]
+                * (2 * lambda + 1) as f64
+                * legendre[lambda];
+        }
+    }
+    projection
+}
+
+/// Returns the coefficients of the monomials x^i y^j z^k, indexed by [i, j, k], in the polynomial
+/// part of a cartesian Gaussian with the given angular vector that is centered at `shift`.
+fn shifted_polynomial(angular: [i32; 3], shift: Vector3<f64>) -> Array3<f64> {
+    let [i, j, k] = angular.map(|l| l as usize);
+    let factors = [0, 1, 2].map(|axis| {
+        let l = angular[axis];
+        (0..=l)
+            .map(|power| binomial(l, power) * (-shift[axis]).powi(l - power))
+            .collect_vec()
+    });
+    Array3::from_shape_fn([i + 1, j + 1, k + 1], |(i, j, k)| {
+        factors[0][i] * factors[1][j] * factors[2][k]
+    })
+}
+
+/// A product grid on the unit sphere, which integrates polynomials of the given degree exactly
+struct AngularGrid {
+    points: Vec<Vector3<f64>>,
+    weights: Vec<f64>,
+}
+
+impl AngularGrid {
+    fn new(degree: usize) -> Self {
+        let (cos_thetas, theta_weights) = gauss_legendre(degree / 2 + 1);
+        let n_phi = degree + 1;
+
+        let mut points = Vec::with_capacity(cos_thetas.len() * n_phi);
+        let mut weights = Vec::with_capacity(cos_thetas.len() * n_phi);
+        for (cos_theta, theta_weight) in cos_thetas.into_iter().zip(theta_weights) {
+            let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+            for k in 0..n_phi {
+                let phi = 2.0 * PI * k as f64 / n_phi as f64;
+                points.push(Vector3::new(
+                    sin_theta * phi.cos(),
+                    sin_theta * phi.sin(),
+                    cos_theta,
+                ));
+                weights.push(theta_weight * 2.0 * PI / n_phi as f64);
+            }
+        }
+        Self { points, weights }
+    }
+}
+
+/// Returns the given vector normalized, or an arbitrary unit vector if it vanishes.
+fn direction(vector: &Vector3<f64>) -> Vector3<f64> {
+    vector.try_normalize(1e-300).unwrap_or_else(Vector3::z)
+}
+
+/// Returns the real spherical harmonics Y_lm for l = 0, ..., l_max at the given unit vector, where
+/// Y_lm has the index l^2 + l + m.
+fn spherical_harmonics(l_max: i32, point: &Vector3<f64>) -> Vec<f64> {
+    let l_max = l_max.max(0) as usize;
+    let mut values = vec![0.0; (l_max + 1).pow(2)];
+
+    // (x + iy)^m = sin^m θ exp(imφ)
+    let (mut real, mut imaginary) = (1.0, 0.0);
+    // the associated Legendre functions P_l^m(cos θ) / sin^m θ, starting at (2m - 1)!! for l = m
+    let mut diagonal = 1.0;
+    for m in 0..=l_max {
+        if m > 0 {
+            (real, imaginary) = (
+                real * point.x - imaginary * point.y,
+                real * point.y + imaginary * point.x,
+            );
+            diagonal *= (2 * m - 1) as f64;
+        }
+
+        let (mut previous, mut current) = (0.0, diagonal);
+        for l in m..=l_max {
+            if l > m {
+                let next = ((2 * l - 1) as f64 * point.z * current - (l + m - 1) as f64 * previous)
+                    / (l - m) as f64;
+                (previous, current) = (current, next);
+            }
+
+            let ratio: f64 = (l - m + 1..=l + m).map(|i| i as f64).product();
+            let norm = ((2 * l + 1) as f64 / (4.0 * PI) / ratio).sqrt();
+            if m == 0 {
+                values[l * l + l] = norm * current;
+            } else {
+                values[l * l + l + m] = std::f64::consts::SQRT_2 * norm * current * real;
+                values[l * l + l - m] = std::f64::consts::SQRT_2 * norm * current * imaginary;
+            }
+        }
+    }
+    values
+}
+
+/// Returns the Legendre polynomials P_l(x) for l = 0, ..., l_max.
+fn legendre(l_max: usize, x: f64) -> Vec<f64> {
+    let mut values = Vec::with_capacity(l_max + 1);
+    values.push(1.0);
+    if l_max > 0 {
+        values.push(x);
+    }
+    for l in 2..=l_max {
+        values.push(
+            ((2 * l - 1) as f64 * x * values[l - 1] - (l - 1) as f64 * values[l - 2]) / l as f64,
+        );
+    }
+    values
+}
+
+/// Returns the scaled modified spherical Bessel functions of the first kind exp(-x) i_l(x) for
+/// l = 0, ..., l_max.
+fn scaled_bessel(l_max: usize, x: f64) -> Vec<f64> {
+    if x < l_max as f64 + 20.0 {
+        // the power series only has positive terms
+        // i_l(x) = x^l / (2l + 1)!! Σ_k (x^2 / 2)^k / (k! (2l + 3) (2l + 5) ... (2l + 2k + 1))
+        let scale = (-x).exp();
+        let mut prefactor = scale;
+        (0..=l_max)
+            .map(|l| {
+                if l > 0 {
+                    prefactor *= x / (2 * l + 1) as f64;
+                }
+                let (mut term, mut sum) = (1.0, 1.0);
+                for k in 1.. {
+                    term *= 0.5 * x * x / (k * (2 * l + 2 * k + 1)) as f64;
+                    sum += term;
+                    if term < 1e-17 * sum {
+                        break;
+                    }
+                }
+                prefactor * sum
+            })
+            .collect()
+    } else {
+        // upward recursion i_{l+1} = i_{l-1} - (2l + 1) / x i_l, which is stable for x > l
+        let decay = (-2.0 * x).exp();
+        let mut values = Vec::with_capacity(l_max + 1);
+        values.push((1.0 - decay) / (2.0 * x));
+        if l_max > 0 {
+            values.push((1.0 + decay) / (2.0 * x) - (1.0 - decay) / (2.0 * x * x));
+        }
+        for l in 1..l_max {
+            values.push(values[l - 1] - (2 * l + 1) as f64 / x * values[l]);
+        }
+        values
+    }
+}
+
+fn binomial(n: i32, k: i32) -> f64 {
+    (1..=k).map(|i| (n - k + i) as f64 / i as f64).product()
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use nalgebra::{DMatrix, DVector, Point3, Rotation3};
+
+    use crate::{
+        basis::{BasisSet, Ecp, EcpTerm},
+        system::{Atom, MolecularSystem},
+    };
+
+    use super::{gauss_legendre, AngularGrid};
+
+    #[test]
+    fn spherical_harmonics() {
+        let grid = AngularGrid::new(16);
+        let harmonics = grid
+            .points
+            .iter()
+            .map(|point| super::spherical_harmonics(8, point))
+            .collect_vec();
+
+        let n = harmonics[0].len();
+        let gram = DMatrix::from_fn(n, n, |i, j| {
+            harmonics
+                .iter()
+                .zip(&grid.weights)
+                .map(|(harmonics, weight)| weight * harmonics[i] * harmonics[j])
+                .sum::<f64>()
+        });
+        approx::assert_abs_diff_eq!(gram, DMatrix::identity(n, n), epsilon = 1e-12);
+    }
+
+    #[test]
+    fn scaled_bessel() {
+        // both the power series and the recursion, which is used above x = 24
+        for x in [0.3, 3.0, 23.9, 24.1, 60.0] {
+            let decay = f64::exp(-2.0 * x);
+            let sinh = 0.5 * (1.0 - decay);
+            let cosh = 0.5 * (1.0 + decay);
+            let expected = [
+                sinh / x,
+                cosh / x - sinh / (x * x),
+                (1.0 + 3.0 / (x * x)) * sinh / x - 3.0 * cosh / (x * x),
+            ];
+
+            let values = super::scaled_bessel(4, x);
+            for (value, expected) in values.iter().zip(expected) {
+                approx::assert_relative_eq!(*value, expected, max_relative = 1e-9);
+            }
+        }
+    }
+
+    /// Compares the integrals to a brute-force quadrature of the potential and the projections on
+    /// a fine grid around the center, which is feasible for diffuse functions only.
+    #[test]
+    fn brute_force() {
+        let basis_set = BasisSet::load("data/basis/6-31G_st.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let term = |power, exponent, coefficient| EcpTerm {
+            power,
+            exponent,
+            coefficient,
+        };
+        let ecp = Ecp {
+            core_electrons: 0,
+            local: vec![term(0, 1.3, -2.0), term(-1, 0.9, 0.7)],
+            semi_local: vec![
+                (0, vec![term(0, 2.1, 3.0), term(-2, 1.5, 0.4)]),
+                (1, vec![term(0, 1.2, -1.5)]),
+                (2, vec![term(1, 0.8, 0.9)]),
+            ],
+        };
+        let center = Point3::new(0.4, -0.3, 0.5);
+        let integrals = super::EcpIntegrals::with_centers(&system, &[(center, &ecp)]);
+
+        // the outer valence and polarization shells
+        let shells = (0..system.n_shells())
+            .map(|shell| system.shell_basis(shell))
+            .filter(|shell| {
+                shell.basis[0]
+                    .exponents
+                    .iter()
+                    .all(|&exponent| exponent < 1.0)
+            })
+            .collect_vec();
+        let functions = shells.iter().flat_map(|shell| {
+            shell
+                .basis
+                .iter()
+                .map(move |function| (shell.center - center, *function))
+        });
+        let functions = functions.collect_vec();
+
+        let (radial_nodes, radial_weights) = gauss_legendre(100);
+        let grid = AngularGrid::new(70);
+        let mut expected = DMatrix::zeros(functions.len(), functions.len());
+        for (node, radial_weight) in radial_nodes.iter().zip(radial_weights) {
+            let r = 4.5 * (node + 1.0);
+            let radial_weight = 4.5 * radial_weight * r * r;
+
+            let values = DMatrix::from_fn(grid.points.len(), functions.len(), |point, function| {
+                let (shift, function) = functions[function];
+                let position = r * grid.points[point] - shift;
+                let polynomial: f64 = (0..3)
+                    .map(|axis| position[axis].powi(function.angular[axis]))
+                    .product();
+                let radial: f64 = function
+                    .iter()
+                    .map(|(coefficient, exponent)| {
+                        coefficient * f64::exp(-exponent * position.norm_squared())
+                    })
+                    .sum();
+                polynomial * radial
+            });
+            let weighted = DMatrix::from_fn(values.nrows(), values.ncols(), |point, function| {
+                grid.weights[point] * values[(point, function)]
+            });
+
+            let local: f64 = ecp.local.iter().map(|term| term.value(r)).sum();
+            expected += radial_weight * local * weighted.transpose() * &values;
+
+            for (l, terms) in &ecp.semi_local {
+                let harmonics =
+                    DMatrix::from_fn(grid.points.len(), 2 * *l as usize + 1, |point, m| {
+                        super::spherical_harmonics(*l, &grid.points[point])[(l * l) as usize + m]
+                    });
+                let projections = weighted.transpose() * harmonics;
+                let potential: f64 = terms.iter().map(|term| term.value(r)).sum();
+                expected += radial_weight * potential * &projections * projections.transpose();
+            }
+        }
+
+        let mut offset_a = 0;
+        for shell_a in &shells {
+            let mut offset_b = 0;
+            for shell_b in &shells {
+                let actual = integrals.compute(*shell_a, *shell_b);
+                let expected = expected.view((offset_a, offset_b), (shell_a.count, shell_b.count));
+                approx::assert_abs_diff_eq!(actual, expected.into_owned(), epsilon = 1e-9);
+                offset_b += shell_b.count;
+            }
+            offset_a += shell_a.count;
+        }
+    }
+
+    /// The effective core potential is spherically symmetric, so that the eigenvalues of its
+    /// matrix in the basis of the (orthonormalized) functions don't depend on the orientation.
+    #[test]
+    fn rotational_invariance() {
+        let basis_set = BasisSet::load("data/basis/def2-SV(P).json").unwrap();
+        let atoms = [
+            Atom::new(37, Point3::new(0.1, 0.2, -0.3)),
+            Atom::new(1, Point3::new(0.1, 0.2, 4.2)),
+        ];
+        let rotation = Rotation3::from_euler_angles(0.3, -0.7, 1.1);
+
+        let [eigenvalues, rotated] = [Rotation3::identity(), rotation].map(|rotation| {
+            let atoms = atoms.map(|atom| Atom::new(atom.ordinal, rotation * atom.position));
+            let system = MolecularSystem::from_atoms(&atoms, &basis_set);
+            let overlap = DMatrix::from(crate::overlap(&system)).cholesky().unwrap();
+            let ecp = DMatrix::from(crate::ecp(&system));
+
+            let inverse = overlap.l().try_inverse().unwrap();
+            let orthonormal = &inverse * ecp * inverse.transpose();
+            let mut eigenvalues = orthonormal.symmetric_eigenvalues().as_slice().to_vec();
+            eigenvalues.sort_by(f64::total_cmp);
+            DVector::from_vec(eigenvalues)
+        });
+        assert!(eigenvalues.amax() > 1.0);
+        approx::assert_abs_diff_eq!(eigenvalues, rotated, epsilon = 1e-9);
+    }
+}
@@ -5,7 +5,7 @@ use ndarray::Array4;
 
 use crate::{
     basis::ContractedGaussian,
-    storage::hermite::{ExpansionCoefficients, HermiteCache},
+    storage::hermite::{ExpansionCoefficients, HermiteCache, HermiteDerivativeCache},
     system::{ShellBasis, ShellType},
 };
 
@@ -13,7 +13,9 @@ use super::utils::coulomb_auxiliary;
 
 /// Computes the electron-electron repulsion energy integral between four [ShellBasis].
 /// Branches for potentially simplifying conditions based on shell types
-/// (for example, (SS|SS) integrals are way simpler to compute than (DD|DD))
+/// (for example, (SS|SS) integrals are way simpler to compute than (DD|DD)).
+/// The expansion coefficients of the pairs (ab| and |cd) are taken from the respective cache,
+/// which differ if the pairs belong to different systems, e.g. for density fitting.
 pub(crate) fn compute_eri(
     basis_a @ ShellBasis {
         shell_type: type_a, ..
@@ -27,13 +29,13 @@ pub(crate) fn compute_eri(
     basis_d @ ShellBasis {
         shell_type: type_d, ..
     }: ShellBasis,
-    hermite_cache: &HermiteCache,
+    hermite_caches: [&HermiteCache; 2],
 ) -> Array4<f64> {
     match (type_a, type_b, type_c, type_d) {
         (ShellType(0), ShellType(0), ShellType(0), ShellType(0)) => {
             ssss::ssss_eri(basis_a, basis_b, basis_c, basis_d)
         }
-        _ => gen_eri(basis_a, basis_b, basis_c, basis_d, hermite_cache),
+        _ => gen_eri(basis_a, basis_b, basis_c, basis_d, hermite_caches),
     }
 }
 
@@ -67,41 +69,32 @@ fn gen_eri(
         count: count_d,
         ..
     }: ShellBasis,
-    hermite_cache: &HermiteCache,
+    [cache_ab, cache_cd]: [&HermiteCache; 2],
 ) -> Array4<f64> {
     let mut result = Array4::zeros((count_a, count_b, count_c, count_d));
 
-    // Symmetry:
-    // when calculating eri (ij|kl), we're ensuring that:
-    //  1. i <= j
-    //  2. k <= l
-    //  3. ij <= kl (hyperindices ij and kl with ab = a * (a + 1) / 2 + b)
-    for global_a in start_a..start_a + basis_a.len() {
-        for global_b in start_b.max(global_a)..start_b + basis_b.len() {
-            let ab = global_a * (global_a + 1) / 2 + global_b;
+    // All integrals of the block are computed, even if some of them are equivalent by symmetry.
+    // This is necessary for spherical shells, which are transformed block-wise. Symmetry is
+    // instead exploited on the level of shell quartets.
+    for global_a in start_a..start_a + count_a {
+        let i = global_a - start_a;
+        let a = basis_a[i];
 
-            let i = global_a - start_a;
+        for global_b in start_b..start_b + count_b {
             let j = global_b - start_b;
-
-            let a = basis_a[i];
             let b = basis_b[j];
 
-            let expansion_ab = hermite_cache.basis_pair(global_a, global_b);
-
-            for global_c in start_c..start_c + basis_c.len() {
-                for global_d in start_d.max(global_c)..start_d + basis_d.len() {
-                    let cd = global_c * (global_c + 1) / 2 + global_d;
+            let expansion_ab = cache_ab.basis_pair(global_a, global_b);
 
-                    if ab > cd {
-                        continue;
-                    }
+            for global_c in start_c..start_c + count_c {
+                let k = global_c - start_c;
+                let c = basis_c[k];
 
-                    let k = global_c - start_c;
+                for global_d in start_d..start_d + count_d {
                     let l = global_d - start_d;
-                    let c = basis_c[k];
                     let d = basis_d[l];
 
-                    let expansion_cd = hermite_cache.basis_pair(global_c, global_d);
+                    let expansion_cd = cache_cd.basis_pair(global_c, global_d);
                     result[(i, j, k, l)] = contracted_gaussian_eri(
                         [a, b, c, d],
                         [pos_a, pos_b, pos_c, pos_d],
@@ -115,6 +108,110 @@ fn gen_eri(
     result
 }
 
+/// Computes the derivatives of the electron-electron repulsion energy integrals between four
+/// [ShellBasis] with respect to the centers of the first three shells. The derivative with respect
+/// to the fourth center follows from translational invariance.
+/// The result is indexed by center and cartesian direction.
+pub(crate) fn compute_eri_derivative(
+    ShellBasis {
+        center: pos_a,
+        basis: basis_a,
+        start_index: start_a,
+        count: count_a,
+        ..
+    }: ShellBasis,
+    ShellBasis {
+        center: pos_b,
+        basis: basis_b,
+        start_index: start_b,
+        count: count_b,
+        ..
+    }: ShellBasis,
+    ShellBasis {
+        center: pos_c,
+        basis: basis_c,
+        start_index: start_c,
+        count: count_c,
+        ..
+    }: ShellBasis,
+    ShellBasis {
+        center: pos_d,
+        basis: basis_d,
+        start_index: start_d,
+        count: count_d,
+        ..
+    }: ShellBasis,
+    hermite_cache: &HermiteCache,
+    derivative_cache: &HermiteDerivativeCache,
+) -> [[Array4<f64>; 3]; 3] {
+    let mut result =
+        [(); 3].map(|_| [(); 3].map(|_| Array4::zeros((count_a, count_b, count_c, count_d))));
+
+    for global_a in start_a..start_a + count_a {
+        let i = global_a - start_a;
+        let a = basis_a[i];
+
+        for global_b in start_b..start_b + count_b {
+            let j = global_b - start_b;
+            let b = basis_b[j];
+
+            let expansion_ab = hermite_cache.basis_pair(global_a, global_b);
+            let terms_a = derivative_cache.basis_pair(global_a, global_b);
+            let terms_b = derivative_cache.basis_pair(global_b, global_a);
+
+            for global_c in start_c..start_c + count_c {
+                let k = global_c - start_c;
+                let c = basis_c[k];
+
+                for global_d in start_d..start_d + count_d {
+                    let l = global_d - start_d;
+                    let d = basis_d[l];
+
+                    let expansion_cd = hermite_cache.basis_pair(global_c, global_d);
+                    let terms_c = derivative_cache.basis_pair(global_c, global_d);
+
+                    for axis in 0..3 {
+                        result[0][axis][(i, j, k, l)] = terms_a[axis]
+                            .iter()
+                            .map(|term| {
+                                contracted_gaussian_eri(
+                                    [&term.basis, b, c, d],
+                                    [pos_a, pos_b, pos_c, pos_d],
+                                    [&term.expansion, expansion_cd],
+                                )
+                            })
+                            .sum();
+                        // (ab'|cd) is evaluated as (b'a|cd), as the derivative terms are only
+                        // stored for the first function of a pair.
+                        result[1][axis][(i, j, k, l)] = terms_b[axis]
+                            .iter()
+                            .map(|term| {
+                                contracted_gaussian_eri(
+                                    [&term.basis, a, c, d],
+                                    [pos_b, pos_a, pos_c, pos_d],
+                                    [&term.expansion, expansion_cd],
+                                )
+                            })
+                            .sum();
+                        result[2][axis][(i, j, k, l)] = terms_c[axis]
+                            .iter()
+                            .map(|term| {
+                                contracted_gaussian_eri(
+                                    [a, b, &term.basis, d],
+                                    [pos_a, pos_b, pos_c, pos_d],
+                                    [expansion_ab, &term.expansion],
+                                )
+                            })
+                            .sum();
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
 fn contracted_gaussian_eri(
     [a, b, c, d]: [&ContractedGaussian; 4],
     [pos_a, pos_b, pos_c, pos_d]: [Point3<f64>; 4],
@@ -139,7 +236,7 @@ fn contracted_gaussian_eri(
                     // inlined utils::product_center to reuse q
                     let product_center_cd = (exp_c * pos_c.coords + exp_d * pos_d.coords) / q;
 
-                    let diff_product = product_center_cd - product_center_ab;
+                    let diff_product = product_center_ab - product_center_cd;
 
                     sum += coeff_a
                         * coeff_b
@@ -8,28 +8,24 @@ pub(super) fn ssss_eri(
     ShellBasis {
         center: pos_a,
         basis: basis_a,
-        start_index: start_a,
         count: count_a,
         ..
     }: ShellBasis,
     ShellBasis {
         center: pos_b,
         basis: basis_b,
-        start_index: start_b,
         count: count_b,
         ..
     }: ShellBasis,
     ShellBasis {
         center: pos_c,
         basis: basis_c,
-        start_index: start_c,
         count: count_c,
         ..
     }: ShellBasis,
     ShellBasis {
         center: pos_d,
         basis: basis_d,
-        start_index: start_d,
         count: count_d,
         ..
     }: ShellBasis,
@@ -39,27 +35,11 @@ pub(super) fn ssss_eri(
 
     let mut result = Array4::zeros((count_a, count_b, count_c, count_d));
 
-    for global_a in start_a..start_a + basis_a.len() {
-        for global_b in start_b.max(global_a)..start_b + basis_b.len() {
-            let ab = global_a * (global_a + 1) / 2 + global_b;
-            for global_c in start_c..start_c + basis_c.len() {
-                for global_d in start_d.max(global_c)..start_d + basis_d.len() {
-                    let cd = global_c * (global_c + 1) / 2 + global_d;
-
-                    if ab > cd {
-                        continue;
-                    }
-
-                    let i = global_a - start_a;
-                    let j = global_b - start_b;
-                    let k = global_c - start_c;
-                    let l = global_d - start_d;
-
-                    let a = basis_a[i];
-                    let b = basis_b[j];
-                    let c = basis_c[k];
-                    let d = basis_d[l];
-
+    // see the comment in gen_eri for why the full block is computed
+    for (i, a) in basis_a.iter().enumerate() {
+        for (j, b) in basis_b.iter().enumerate() {
+            for (k, c) in basis_c.iter().enumerate() {
+                for (l, d) in basis_d.iter().enumerate() {
                     result[(i, j, k, l)] = contracted_gaussian_eri(
                         [a, b, c, d],
                         [pos_a, pos_b, pos_c, pos_d],
@@ -1,6 +1,9 @@
 use nalgebra::{DMatrix, Vector3};
 
-use crate::{integrals::utils::hermite_expansion, system::ShellBasis};
+use crate::{
+    integrals::utils::{center_derivative, primitive_overlap},
+    system::ShellBasis,
+};
 
 /// Function to compute the kinetic energy integrals between two electron shells of arbitrary type
 pub(crate) fn compute_kinetic(
@@ -36,7 +39,7 @@ fn gen_kinetic(
     // a lot of stuff that is helpful to understand this function is documented in the gen_overlap
     // function in 'src/integrals/overlap/mod.rs'.
 
-    let diff = pos_b - pos_a;
+    let diff = pos_a - pos_b;
 
     let mut result = DMatrix::zeros(count_a, count_b);
 
@@ -50,36 +53,13 @@ fn gen_kinetic(
             let a = basis_a[i];
             let b = basis_b[j];
 
-            let [l1, m1, n1] = a.angular;
-            let [l2, m2, n2] = b.angular;
-
             let mut sum = 0.0;
 
             for (coeff_a, exp_a) in a.iter() {
                 for (coeff_b, exp_b) in b.iter() {
-                    // don't know a good name to call this
-                    let angular_step = |i: i32, j: i32, k: i32| {
-                        primitive_overlap(
-                            exp_a,
-                            exp_b,
-                            (l1, m1, n1),
-                            (l2 + i, m2 + j, n2 + k),
-                            diff,
-                        )
-                    };
-
-                    let term0 = exp_b
-                        * (2.0 * (l2 + m2 + n2) as f64 + 3.0)
-                        * primitive_overlap(exp_a, exp_b, (l1, m1, n1), (l2, m2, n2), diff);
-                    let term1 = -2.0
-                        * exp_b.powi(2)
-                        * (angular_step(2, 0, 0) + angular_step(0, 2, 0) + angular_step(0, 0, 2));
-                    let term2 = -0.5
-                        * ((l2 * (l2 - 1)) as f64 * angular_step(-2, 0, 0)
-                            + (m2 * (m2 - 1)) as f64 * angular_step(0, -2, 0)
-                            + (n2 * (n2 - 1)) as f64 * angular_step(0, 0, -2));
-
-                    sum += coeff_a * coeff_b * (term0 + term1 + term2);
+                    sum += coeff_a
+                        * coeff_b
+                        * primitive_kinetic(exp_a, exp_b, a.angular, b.angular, diff);
                 }
             }
 
@@ -90,15 +70,69 @@ fn gen_kinetic(
     result
 }
 
-fn primitive_overlap(
+/// Function to compute the derivatives <∂a/∂A_i|T|b> of the kinetic energy integrals between two
+/// electron shells with respect to the center A of the first shell, for all three directions i.
+/// In contrast to [compute_kinetic], the full block is computed.
+pub(crate) fn compute_kinetic_derivative(
+    ShellBasis {
+        center: pos_a,
+        basis: basis_a,
+        count: count_a,
+        ..
+    }: ShellBasis,
+    ShellBasis {
+        center: pos_b,
+        basis: basis_b,
+        count: count_b,
+        ..
+    }: ShellBasis,
+) -> [DMatrix<f64>; 3] {
+    let diff = pos_a - pos_b;
+
+    let mut result = [(); 3].map(|_| DMatrix::zeros(count_a, count_b));
+
+    for (i, a) in basis_a.iter().enumerate() {
+        for (j, b) in basis_b.iter().enumerate() {
+            for (coeff_a, exp_a) in a.iter() {
+                for (coeff_b, exp_b) in b.iter() {
+                    for (axis, result) in result.iter_mut().enumerate() {
+                        result[(i, j)] += coeff_a
+                            * coeff_b
+                            * center_derivative(a.angular, exp_a, axis, |angular_a| {
+                                primitive_kinetic(exp_a, exp_b, angular_a, b.angular, diff)
+                            });
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Kinetic energy integral between two primitive gaussians
+fn primitive_kinetic(
     exp_a: f64,
     exp_b: f64,
-    (l1, m1, n1): (i32, i32, i32),
-    (l2, m2, n2): (i32, i32, i32),
+    [l1, m1, n1]: [i32; 3],
+    [l2, m2, n2]: [i32; 3],
     diff: Vector3<f64>,
 ) -> f64 {
-    (std::f64::consts::PI / (exp_a + exp_b)).powi(3).sqrt()
-        * hermite_expansion([l1, l2, 0], diff.x, exp_a, exp_b)
-        * hermite_expansion([m1, m2, 0], diff.y, exp_a, exp_b)
-        * hermite_expansion([n1, n2, 0], diff.z, exp_a, exp_b)
+    // don't know a good name to call this
+    let angular_step = |i: i32, j: i32, k: i32| {
+        primitive_overlap(exp_a, exp_b, (l1, m1, n1), (l2 + i, m2 + j, n2 + k), diff)
+    };
+
+    let term0 = exp_b
+        * (2.0 * (l2 + m2 + n2) as f64 + 3.0)
+        * primitive_overlap(exp_a, exp_b, (l1, m1, n1), (l2, m2, n2), diff);
+    let term1 = -2.0
+        * exp_b.powi(2)
+        * (angular_step(2, 0, 0) + angular_step(0, 2, 0) + angular_step(0, 0, 2));
+    let term2 = -0.5
+        * ((l2 * (l2 - 1)) as f64 * angular_step(-2, 0, 0)
+            + (m2 * (m2 - 1)) as f64 * angular_step(0, -2, 0)
+            + (n2 * (n2 - 1)) as f64 * angular_step(0, 0, -2));
+
+    term0 + term1 + term2
 }
@@ -1,39 +1,39 @@
 //! This module contains the definitions of all logic associated with integral evaluation
 
-use std::time::Instant;
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+use anyhow::Context;
+use smallvec::smallvec;
 
 use crate::{
-    storage::{hermite::HermiteCache, EriTensor, SymmetricMatrix},
-    system::{MolecularSystem, ShellBasis},
+    basis::{ContractedGaussian, FunctionType},
+    storage::{
+        hermite::{HermiteCache, HermiteDerivativeCache},
+        EriDerivativeTensor, EriTensor, JkAccumulator, SymmetricMatrix,
+    },
+    system::{MolecularSystem, ShellBasis, ShellType},
 };
-use nalgebra::DMatrix;
+use itertools::Itertools;
+use nalgebra::{DMatrix, DVector, Point3};
 
+mod ecp;
 mod eri;
 mod kinetic;
+mod multipole;
 mod nuclear;
 mod overlap;
+mod parallel;
 mod screening;
+mod spherical;
 mod utils;
+pub(crate) mod values;
 
 /// Computes and returns the overlap integral matrix for the given [MolecularSystem] as a [SymmetricMatrix].
 pub fn overlap(system: &MolecularSystem) -> SymmetricMatrix {
-    let mut output = SymmetricMatrix::zeros(system.n_basis());
-    for a in 0..system.shells.len() {
-        let basis_a @ ShellBasis {
-            start_index: start_a,
-            count: count_a,
-            ..
-        } = system.shell_basis(a);
-        for b in a..system.shells.len() {
-            let basis_b @ ShellBasis {
-                start_index: start_b,
-                count: count_b,
-                ..
-            } = system.shell_basis(b);
-            let result = overlap::compute_overlap(basis_a, basis_b);
-            output.copy_from(&result, (start_a, start_b), (count_a, count_b));
-        }
-    }
+    let output = one_electron(system, overlap::compute_overlap);
     let log_level = log::Level::Trace;
     if log::log_enabled!(log_level) {
         log::log!(
@@ -48,23 +48,7 @@ pub fn overlap(system: &MolecularSystem) -> SymmetricMatrix {
 
 /// Returns the kinetic energy integral matrix for the given [MolecularSystem] as a [SymmetricMatrix]
 pub fn kinetic(system: &MolecularSystem) -> SymmetricMatrix {
-    let mut output = SymmetricMatrix::zeros(system.n_basis());
-    for a in 0..system.shells.len() {
-        let basis_a @ ShellBasis {
-            start_index: start_a,
-            count: count_a,
-            ..
-        } = system.shell_basis(a);
-        for b in a..system.shells.len() {
-            let basis_b @ ShellBasis {
-                start_index: start_b,
-                count: count_b,
-                ..
-            } = system.shell_basis(b);
-            let result = kinetic::compute_kinetic(basis_a, basis_b);
-            output.copy_from(&result, (start_a, start_b), (count_a, count_b));
-        }
-    }
+    let output = one_electron(system, kinetic::compute_kinetic);
     let log_level = log::Level::Trace;
     if log::log_enabled!(log_level) {
         log::log!(
@@ -80,27 +64,414 @@ pub fn kinetic(system: &MolecularSystem) -> SymmetricMatrix {
 /// Returns the electron-nuclear attraction energy integral matrix for the given [MolecularSystem] as a
 /// [SymmetricMatrix]
 pub fn nuclear(system: &MolecularSystem) -> SymmetricMatrix {
-    let mut output = SymmetricMatrix::zeros(system.n_basis());
-    for a in 0..system.shells.len() {
-        let basis_a @ ShellBasis {
-            start_index: start_a,
-            count: count_a,
-            ..
-        } = system.shell_basis(a);
-        for b in a..system.shells.len() {
-            let basis_b @ ShellBasis {
-                start_index: start_b,
-                count: count_b,
-                ..
-            } = system.shell_basis(b);
+    let output = one_electron(system, |basis_a, basis_b| {
+        nuclear::compute_nuclear(basis_a, basis_b, system)
+    });
+    let log_level = log::Level::Trace;
+    if log::log_enabled!(log_level) {
+        log::log!(log_level, "nuclear: {:2.4}", DMatrix::from(&output));
+    }
+    output
+}
 
-            let result = nuclear::compute_nuclear(basis_a, basis_b, system);
-            output.copy_from(&result, (start_a, start_b), (count_a, count_b))
+/// Returns the electrostatic potential integral matrices <a|1/|r - C||b> at each of the given
+/// points C. The potential of the electrons at C is -tr(D V_C) for a density matrix D, as used,
+/// e.g., for fitting atomic charges to the electrostatic potential.
+pub fn potential(system: &MolecularSystem, points: &[Point3<f64>]) -> Vec<SymmetricMatrix> {
+    one_electron_components(system, points.len(), |basis_a, basis_b| {
+        nuclear::compute_point_integrals(basis_a, basis_b, points, false)
+    })
+}
+
+/// Returns the electric field integral matrices <a|(C - r)/|r - C|^3|b> = -∇_C <a|1/|r - C||b> at
+/// each of the given points C, indexed by point and cartesian direction. The field of the
+/// electrons at C is -tr(D E_C) for a density matrix D.
+pub fn electric_field(
+    system: &MolecularSystem,
+    points: &[Point3<f64>],
+) -> Vec<[SymmetricMatrix; 3]> {
+    let mut components = one_electron_components(system, 3 * points.len(), |basis_a, basis_b| {
+        nuclear::compute_point_integrals(basis_a, basis_b, points, true)
+    })
+    .into_iter();
+    points
+        .iter()
+        .map(|_| [(); 3].map(|_| components.next().unwrap()))
+        .collect()
+}
+
+/// Returns the electrostatic potential of the nuclei and of the electrons with the given (total)
+/// density matrix at each of the given points, without storing an integral matrix per point.
+/// The nuclei follow the [NuclearModel](crate::system::NuclearModel) of the system, and external
+/// point charges are not included.
+pub fn electrostatic_potential(
+    system: &MolecularSystem,
+    density: &SymmetricMatrix,
+    points: &[Point3<f64>],
+) -> Vec<f64> {
+    let density = DMatrix::from(density);
+    let shell_pairs = shell_pairs(system);
+    let contributions = parallel::map(&shell_pairs, |&(a, b)| {
+        let basis_a = system.shell_basis(a);
+        let basis_b = system.shell_basis(b);
+        let density = density.view(
+            (basis_a.matrix_start_index, basis_b.matrix_start_index),
+            (basis_a.matrix_count, basis_b.matrix_count),
+        );
+        // the pair (b, a) contributes the same as (a, b)
+        let factor = if a == b { -1.0 } else { -2.0 };
+
+        nuclear::compute_point_integrals(basis_a, basis_b, points, false)
+            .into_iter()
+            .map(|block| {
+                factor * spherical::transform_pair(block, &basis_a, &basis_b).dot(&density)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mut output: Vec<f64> = points
+        .iter()
+        .map(|point| {
+            system
+                .attraction_centers()
+                .take(system.atoms.len())
+                .filter(|&(charge, _, _)| charge != 0.0)
+                .map(|(charge, position, exponent)| {
+                    let distance_squared = (position - point).norm_squared();
+                    match exponent {
+                        None => charge / distance_squared.sqrt(),
+                        // erf(sqrt(ζ) r) / r of a Gaussian nucleus, which is finite at r = 0
+                        Some(zeta) => {
+                            charge
+                                * 2.0
+                                * (zeta / std::f64::consts::PI).sqrt()
+                                * boys::micb25::boys(0, zeta * distance_squared)
+                        }
+                    }
+                })
+                .sum()
+        })
+        .collect();
+    for contributions in contributions {
+        for (output, contribution) in output.iter_mut().zip(contributions) {
+            *output += contribution;
         }
     }
+    output
+}
+
+/// Returns the values of all basis functions of the given [MolecularSystem] at the given points,
+/// with one row per point and one column per basis function.
+pub fn basis_values(system: &MolecularSystem, points: &[Point3<f64>]) -> DMatrix<f64> {
+    basis_derivatives(system, points, 0).remove(0)
+}
+
+/// Returns the values of all basis functions of the given [MolecularSystem] and their derivatives
+/// with respect to the electron coordinates up to the given order at the given points, with one
+/// row per point and one column per basis function. The result contains the values, then the
+/// gradient (x, y, z), then the Hessian (xx, xy, xz, yy, yz, zz) and so on, with the components of
+/// each order as in [multipole_components]. Shells are skipped at points where they (and their
+/// derivatives) are negligible.
+pub fn basis_derivatives(
+    system: &MolecularSystem,
+    points: &[Point3<f64>],
+    order: usize,
+) -> Vec<DMatrix<f64>> {
+    let chunks = values::map_chunks(system, points, order, |_, derivatives| derivatives);
+    (0..values::derivative_components(order).len())
+        .map(|component| {
+            stack_rows(
+                chunks.iter().map(|derivatives| &derivatives[component]),
+                system.n_basis(),
+            )
+        })
+        .collect()
+}
+
+/// Returns the values of the molecular orbitals with the given coefficients (one orbital per
+/// column) at the given points, with one row per point and one column per orbital.
+pub fn orbital_values(
+    system: &MolecularSystem,
+    coefficients: &DMatrix<f64>,
+    points: &[Point3<f64>],
+) -> DMatrix<f64> {
+    let chunks = values::map_chunks(system, points, 0, |_, values| &values[0] * coefficients);
+    stack_rows(&chunks, coefficients.ncols())
+}
+
+/// Returns the electron density of the given (total) density matrix at each of the given points.
+pub fn electron_density(
+    system: &MolecularSystem,
+    density: &SymmetricMatrix,
+    points: &[Point3<f64>],
+) -> Vec<f64> {
+    let density = DMatrix::from(density);
+    values::map_chunks(system, points, 0, |_, values| {
+        let products = &values[0] * &density;
+        values[0]
+            .row_iter()
+            .zip(products.row_iter())
+            .map(|(values, products)| values.dot(&products))
+            .collect::<Vec<_>>()
+    })
+    .concat()
+}
+
+/// Stacks the given matrices with the given number of columns on top of each other.
+fn stack_rows<'a>(
+    blocks: impl IntoIterator<Item = &'a DMatrix<f64>> + Clone,
+    n_columns: usize,
+) -> DMatrix<f64> {
+    let n_rows = blocks.clone().into_iter().map(DMatrix::nrows).sum();
+    let mut output = DMatrix::zeros(n_rows, n_columns);
+    let mut start = 0;
+    for block in blocks {
+        output.rows_mut(start, block.nrows()).copy_from(block);
+        start += block.nrows();
+    }
+    output
+}
+
+/// Returns the integral matrix of the effective core potentials of the atoms of the given
+/// [MolecularSystem] as a [SymmetricMatrix], which vanishes unless its basis set uses effective
+/// core potentials. Their derivatives are not available yet.
+pub fn ecp(system: &MolecularSystem) -> SymmetricMatrix {
+    let integrals = ecp::EcpIntegrals::new(system);
+    if integrals.is_empty() {
+        return SymmetricMatrix::zeros(system.n_basis());
+    }
+
+    let output = one_electron(system, |basis_a, basis_b| {
+        integrals.compute(basis_a, basis_b)
+    });
+    let log_level = log::Level::Trace;
+    if log::log_enabled!(log_level) {
+        log::log!(log_level, "ecp: {:2.4}", DMatrix::from(&output));
+    }
+    output
+}
+
+/// Returns the derivatives of the overlap integral matrix with respect to the positions of the
+/// atoms of the given [MolecularSystem]. The result is indexed by atom and cartesian direction.
+pub fn overlap_derivatives(system: &MolecularSystem) -> Vec<[SymmetricMatrix; 3]> {
+    one_electron_derivatives(system, |basis_a, basis_b| {
+        let derivative_a = overlap::compute_overlap_derivative(basis_a, basis_b);
+        let derivative_b = overlap::compute_overlap_derivative(basis_b, basis_a);
+        vec![
+            (basis_a.atom_index, derivative_a),
+            (basis_b.atom_index, derivative_b.map(|d| d.transpose())),
+        ]
+    })
+}
+
+/// Returns the derivatives of the kinetic energy integral matrix with respect to the positions of
+/// the atoms of the given [MolecularSystem]. The result is indexed by atom and cartesian direction.
+pub fn kinetic_derivatives(system: &MolecularSystem) -> Vec<[SymmetricMatrix; 3]> {
+    one_electron_derivatives(system, |basis_a, basis_b| {
+        let derivative_a = kinetic::compute_kinetic_derivative(basis_a, basis_b);
+        let derivative_b = kinetic::compute_kinetic_derivative(basis_b, basis_a);
+        vec![
+            (basis_a.atom_index, derivative_a),
+            (basis_b.atom_index, derivative_b.map(|d| d.transpose())),
+        ]
+    })
+}
+
+/// Returns the derivatives of the electron-nuclear attraction energy integral matrix with respect
+/// to the positions of the atoms of the given [MolecularSystem]. The result is indexed by atom and
+/// cartesian direction.
+///
+/// Besides the derivatives of the basis functions, this includes the derivative of the attraction
+/// operator itself with respect to the position of each nucleus. The attraction to external point
+/// charges only enters through the derivatives of the basis functions.
+pub fn nuclear_derivatives(system: &MolecularSystem) -> Vec<[SymmetricMatrix; 3]> {
+    one_electron_derivatives(system, |basis_a, basis_b| {
+        let derivatives_a = nuclear::compute_nuclear_derivative(basis_a, basis_b, system);
+        let derivatives_b = nuclear::compute_nuclear_derivative(basis_b, basis_a, system);
+
+        let zeros = || [(); 3].map(|_| DMatrix::zeros(basis_a.count, basis_b.count));
+        let mut total_a = zeros();
+        let mut total_b = zeros();
+
+        let mut contributions = Vec::with_capacity(system.atoms.len() + 2);
+        for (atom_index, (derivative_a, derivative_b)) in
+            derivatives_a.into_iter().zip(derivatives_b).enumerate()
+        {
+            let derivative_b = derivative_b.map(|d| d.transpose());
+
+            // The attraction to a single nucleus is invariant under translation of all three
+            // centers, so the derivative with respect to the nucleus follows from the other two.
+            let mut derivative_nucleus = zeros();
+            for axis in 0..3 {
+                derivative_nucleus[axis] -= &derivative_a[axis] + &derivative_b[axis];
+                total_a[axis] += &derivative_a[axis];
+                total_b[axis] += &derivative_b[axis];
+            }
+            // the remaining centers are external point charges, which aren't differentiated for
+            if atom_index < system.atoms.len() {
+                contributions.push((atom_index, derivative_nucleus));
+            }
+        }
+        contributions.push((basis_a.atom_index, total_a));
+        contributions.push((basis_b.atom_index, total_b));
+        contributions
+    })
+}
+
+/// Returns the cartesian multipole moment integral matrices <a|(x - O_x)^i (y - O_y)^j (z - O_z)^k|b>
+/// about the given origin O, for all moments of order 1 up to and including `order`.
+///
+/// The components are ordered by increasing order, and within each order as given by
+/// [multipole_components], i.e., x, y, z, xx, xy, xz, yy, yz, zz, xxx, ...
+pub fn multipole(
+    system: &MolecularSystem,
+    origin: Point3<f64>,
+    order: usize,
+) -> Vec<SymmetricMatrix> {
+    let components: Vec<_> = (1..=order).flat_map(multipole_components).collect();
+    let output = one_electron_components(system, components.len(), |basis_a, basis_b| {
+        multipole::compute_multipole(basis_a, basis_b, origin, &components)
+    });
     let log_level = log::Level::Trace;
     if log::log_enabled!(log_level) {
-        log::log!(log_level, "nuclear: {:2.4}", DMatrix::from(&output));
+        for (component, matrix) in components.iter().zip(&output) {
+            log::log!(
+                log_level,
+                "multipole {component:?}: {:2.4}",
+                DMatrix::from(matrix)
+            );
+        }
+    }
+    output
+}
+
+/// Returns the exponents [i, j, k] of all cartesian multipole moment components x^i y^j z^k of the
+/// given order, in the order they are returned by [multipole].
+pub fn multipole_components(order: usize) -> Vec<[i32; 3]> {
+    let order = order as i32;
+    (0..=order)
+        .rev()
+        .flat_map(|i| (0..=order - i).rev().map(move |j| [i, j, order - i - j]))
+        .collect()
+}
+
+/// Builds a one-electron integral matrix by evaluating the given integral for all pairs of shells
+/// and transforming the results into the basis of the [MolecularSystem].
+///
+/// The integral function only has to compute entries (i, j) with i <= j if both shells are equal.
+fn one_electron(
+    system: &MolecularSystem,
+    integral: impl Fn(ShellBasis, ShellBasis) -> DMatrix<f64> + Send + Sync,
+) -> SymmetricMatrix {
+    one_electron_components(system, 1, |basis_a, basis_b| {
+        vec![integral(basis_a, basis_b)]
+    })
+    .remove(0)
+}
+
+/// Like [one_electron], but for integrals with several components (e.g., multipole moments), which
+/// are computed at once for each pair of shells.
+fn one_electron_components(
+    system: &MolecularSystem,
+    n_components: usize,
+    integral: impl Fn(ShellBasis, ShellBasis) -> Vec<DMatrix<f64>> + Send + Sync,
+) -> Vec<SymmetricMatrix> {
+    let shell_pairs = shell_pairs(system);
+    let results = parallel::map(&shell_pairs, |&(a, b)| {
+        let basis_a = system.shell_basis(a);
+        let basis_b = system.shell_basis(b);
+
+        integral(basis_a, basis_b)
+            .into_iter()
+            .map(|mut result| {
+                if a == b {
+                    result.fill_lower_triangle_with_upper_triangle();
+                }
+                spherical::transform_pair(result, &basis_a, &basis_b)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mut output: Vec<_> = (0..n_components)
+        .map(|_| SymmetricMatrix::zeros(system.n_basis()))
+        .collect();
+    for ((a, b), results) in shell_pairs.into_iter().zip(results) {
+        let ShellBasis {
+            matrix_start_index: start_a,
+            matrix_count: count_a,
+            ..
+        } = system.shell_basis(a);
+        let ShellBasis {
+            matrix_start_index: start_b,
+            matrix_count: count_b,
+            ..
+        } = system.shell_basis(b);
+
+        for (output, result) in output.iter_mut().zip(results) {
+            output.copy_from(&result, (start_a, start_b), (count_a, count_b));
+        }
+    }
+    output
+}
+
+/// Builds the derivatives of a one-electron integral matrix with respect to the positions of all
+/// atoms. For each pair of shells, the given function returns the (full, cartesian) derivative
+/// blocks along with the index of the atom they are a derivative with respect to. Blocks that
+/// belong to the same atom are summed up.
+fn one_electron_derivatives(
+    system: &MolecularSystem,
+    derivative: impl Fn(ShellBasis, ShellBasis) -> Vec<(usize, [DMatrix<f64>; 3])> + Send + Sync,
+) -> Vec<[SymmetricMatrix; 3]> {
+    let shell_pairs = shell_pairs(system);
+    let results = parallel::map(&shell_pairs, |&(a, b)| {
+        let basis_a = system.shell_basis(a);
+        let basis_b = system.shell_basis(b);
+
+        let mut contributions = derivative(basis_a, basis_b);
+        contributions.sort_by_key(|&(atom_index, _)| atom_index);
+
+        let mut merged: Vec<(usize, [DMatrix<f64>; 3])> = Vec::new();
+        for (atom_index, blocks) in contributions {
+            match merged.last_mut() {
+                Some((last_index, merged_blocks)) if *last_index == atom_index => {
+                    for (merged_block, block) in merged_blocks.iter_mut().zip(blocks) {
+                        *merged_block += block;
+                    }
+                }
+                _ => merged.push((atom_index, blocks)),
+            }
+        }
+
+        merged
+            .into_iter()
+            .map(|(atom_index, blocks)| {
+                let blocks =
+                    blocks.map(|block| spherical::transform_pair(block, &basis_a, &basis_b));
+                (atom_index, blocks)
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let mut output: Vec<_> = (0..system.atoms.len())
+        .map(|_| [(); 3].map(|_| SymmetricMatrix::zeros(system.n_basis())))
+        .collect();
+    for ((a, b), results) in shell_pairs.into_iter().zip(results) {
+        let ShellBasis {
+            matrix_start_index: start_a,
+            matrix_count: count_a,
+            ..
+        } = system.shell_basis(a);
+        let ShellBasis {
+            matrix_start_index: start_b,
+            matrix_count: count_b,
+            ..
+        } = system.shell_basis(b);
+
+        for (atom_index, blocks) in results {
+            for (output, block) in output[atom_index].iter_mut().zip(blocks) {
+                output.copy_from(&block, (start_a, start_b), (count_a, count_b));
+            }
+        }
     }
     output
 }
@@ -118,98 +489,1182 @@ pub fn eri(system: &MolecularSystem) -> EriTensor {
     );
 
     let start = Instant::now();
+    let shell_norms = shell_norms(system, &hermite_cache);
+    let diagonal_duration = start.elapsed();
+    let prediction = diagonal_duration * n_shells.pow(2) as u32 / 8;
+    println!("done precomputing diagonal for screening. took {diagonal_duration:3.3?}. Full is thus estimated to take {prediction:3.3?}");
+
     let mut output = EriTensor::zeros(system.n_basis());
-    let mut shell_norms = SymmetricMatrix::zeros(n_shells);
-    for a in 0..n_shells {
-        for b in a..n_shells {
-            let basis_a @ ShellBasis {
-                start_index: start_a,
-                count: count_a,
-                ..
-            } = system.shell_basis(a);
-            let basis_b @ ShellBasis {
-                start_index: start_b,
-                count: count_b,
-                ..
-            } = system.shell_basis(b);
-
-            let result = eri::compute_eri(basis_a, basis_b, basis_a, basis_b, &hermite_cache);
-
-            shell_norms[(a, b)] = screening::shell_norm(&result);
+    let (screened, total) = for_each_screened_quartet(
+        system,
+        |[a, b, c, d]| {
+            shell_norms[(a, b)] * shell_norms[(c, d)] < screening::SUFFICIENTLY_SMALL_THRESHOLD
+        },
+        |quartet| {
+            let [basis_a, basis_b, basis_c, basis_d] = quartet;
+            let result = eri::compute_eri(basis_a, basis_b, basis_c, basis_d, [&hermite_cache; 2]);
+            spherical::transform_quartet(result, quartet.each_ref())
+        },
+        |[basis_a, basis_b, basis_c, basis_d], result| {
+            output.copy_from(
+                &result,
+                (
+                    basis_a.matrix_start_index,
+                    basis_b.matrix_start_index,
+                    basis_c.matrix_start_index,
+                    basis_d.matrix_start_index,
+                ),
+                (
+                    basis_a.matrix_count,
+                    basis_b.matrix_count,
+                    basis_c.matrix_count,
+                    basis_d.matrix_count,
+                ),
+            )
+        },
+    );
 
+    println!(
+        "screened {screened} out of {total} shell quartets ({:3.1}%)",
+        screened as f32 / total as f32 * 100.0
+    );
+    println!("computing full ERI tensor took {:3.3?}", start.elapsed());
+    output
+}
+
+/// Returns the derivatives of the electron-electron repulsion energy integrals for the given
+/// [MolecularSystem] with respect to the centers of the basis functions as an
+/// [EriDerivativeTensor]. Shell quartets are screened in the same way as in [eri].
+pub fn eri_derivatives(system: &MolecularSystem) -> EriDerivativeTensor {
+    let start = Instant::now();
+    let hermite_cache = HermiteCache::new(system);
+    let derivative_cache = HermiteDerivativeCache::new(system);
+    log::debug!(
+        "computing hermite expansion coefficient caches took {:3.3?}",
+        start.elapsed()
+    );
+
+    let start = Instant::now();
+    let shell_norms = shell_norms(system, &hermite_cache);
+
+    let mut output = EriDerivativeTensor::zeros(system);
+    for_each_screened_quartet(
+        system,
+        |[a, b, c, d]| {
+            shell_norms[(a, b)] * shell_norms[(c, d)] < screening::SUFFICIENTLY_SMALL_THRESHOLD
+        },
+        |quartet| {
+            let [basis_a, basis_b, basis_c, basis_d] = quartet;
+            eri::compute_eri_derivative(
+                basis_a,
+                basis_b,
+                basis_c,
+                basis_d,
+                &hermite_cache,
+                &derivative_cache,
+            )
+            .map(|center| {
+                center.map(|block| spherical::transform_quartet(block, quartet.each_ref()))
+            })
+        },
+        |[basis_a, basis_b, basis_c, basis_d], result| {
             output.copy_from(
                 &result,
-                (start_a, start_b, start_a, start_b),
-                (count_a, count_b, count_a, count_b),
+                (
+                    basis_a.matrix_start_index,
+                    basis_b.matrix_start_index,
+                    basis_c.matrix_start_index,
+                    basis_d.matrix_start_index,
+                ),
+                (
+                    basis_a.matrix_count,
+                    basis_b.matrix_count,
+                    basis_c.matrix_count,
+                    basis_d.matrix_count,
+                ),
+            )
+        },
+    );
+
+    log::debug!("computing ERI derivatives took {:3.3?}", start.elapsed());
+    output
+}
+
+/// Returns the two-center electron repulsion integrals (P|Q) between the basis functions of the
+/// given auxiliary [MolecularSystem], i.e., the Coulomb metric of density fitting. Auxiliary basis
+/// sets, such as the `-jkfit` and `-rifit` sets of the basis set exchange, are loaded like orbital
+/// basis sets, and the auxiliary system is usually built from the same atoms as the orbital one.
+pub fn eri_two_center(auxiliary: &MolecularSystem) -> SymmetricMatrix {
+    let unit = unit_function();
+    let unit_basis = [&unit];
+    let hermite_cache = HermiteCache::with_unit(auxiliary, &unit);
+
+    let shell_pairs = shell_pairs(auxiliary);
+    let results = parallel::map(&shell_pairs, |&(p, q)| {
+        let basis_p = auxiliary.shell_basis(p);
+        let basis_q = auxiliary.shell_basis(q);
+        let unit_p = unit_shell(&basis_p, &unit_basis);
+        let unit_q = unit_shell(&basis_q, &unit_basis);
+
+        let result = eri::compute_eri(basis_p, unit_p, basis_q, unit_q, [&hermite_cache; 2]);
+        let result = spherical::transform_quartet(result, [&basis_p, &unit_p, &basis_q, &unit_q]);
+        DMatrix::from_fn(basis_p.matrix_count, basis_q.matrix_count, |i, j| {
+            result[(i, 0, j, 0)]
+        })
+    });
+
+    let mut output = SymmetricMatrix::zeros(auxiliary.n_basis());
+    for ((p, q), block) in shell_pairs.into_iter().zip(results) {
+        let basis_p = auxiliary.shell_basis(p);
+        let basis_q = auxiliary.shell_basis(q);
+        output.copy_from(
+            &block,
+            (basis_p.matrix_start_index, basis_q.matrix_start_index),
+            (basis_p.matrix_count, basis_q.matrix_count),
+        );
+    }
+    output
+}
+
+/// Returns the three-center electron repulsion integrals (P|ab) between the basis functions P of
+/// the given auxiliary [MolecularSystem] and the pairs of basis functions a, b of the given
+/// system, as one matrix over the basis of the system per auxiliary function. Shell triplets are
+/// skipped if their Schwarz bound sqrt((P|P) (ab|ab)) is negligible.
+pub fn eri_three_center(
+    system: &MolecularSystem,
+    auxiliary: &MolecularSystem,
+) -> Vec<SymmetricMatrix> {
+    let start = Instant::now();
+    let unit = unit_function();
+    let unit_basis = [&unit];
+    let auxiliary_cache = HermiteCache::with_unit(auxiliary, &unit);
+    let hermite_cache = HermiteCache::new(system);
+    let shell_norms = shell_norms(system, &hermite_cache);
+    let shell_pairs = shell_pairs(system);
+
+    let auxiliary_shells: Vec<_> = (0..auxiliary.n_shells()).collect();
+    let results = parallel::map(&auxiliary_shells, |&p| {
+        let basis_p = auxiliary.shell_basis(p);
+        let unit_p = unit_shell(&basis_p, &unit_basis);
+        let diagonal = eri::compute_eri(basis_p, unit_p, basis_p, unit_p, [&auxiliary_cache; 2]);
+        let norm_p = screening::shell_norm(&spherical::transform_quartet(
+            diagonal,
+            [&basis_p, &unit_p, &basis_p, &unit_p],
+        ));
+
+        let mut output = vec![SymmetricMatrix::zeros(system.n_basis()); basis_p.matrix_count];
+        for &(a, b) in &shell_pairs {
+            if norm_p * shell_norms[(a, b)] < screening::SUFFICIENTLY_SMALL_THRESHOLD {
+                continue;
+            }
+            let basis_a = system.shell_basis(a);
+            let basis_b = system.shell_basis(b);
+
+            let result = eri::compute_eri(
+                basis_p,
+                unit_p,
+                basis_a,
+                basis_b,
+                [&auxiliary_cache, &hermite_cache],
             );
+            let result =
+                spherical::transform_quartet(result, [&basis_p, &unit_p, &basis_a, &basis_b]);
+            for (i, output) in output.iter_mut().enumerate() {
+                let block = DMatrix::from_fn(basis_a.matrix_count, basis_b.matrix_count, |j, k| {
+                    result[(i, 0, j, k)]
+                });
+                output.copy_from(
+                    &block,
+                    (basis_a.matrix_start_index, basis_b.matrix_start_index),
+                    (basis_a.matrix_count, basis_b.matrix_count),
+                );
+            }
+        }
+        output
+    });
+
+    log::debug!(
+        "computing three-center integrals took {:3.3?}",
+        start.elapsed()
+    );
+    results.concat()
+}
+
+/// Returns the density fitting (resolution of the identity) tensor
+/// B^Q_ab = sum_P (L^-1)_QP (P|ab) of the given system with the given auxiliary system, where L is
+/// the Cholesky factor of the Coulomb metric (P|Q) = (L L^T)_PQ, as one matrix over the basis of
+/// the system per auxiliary function. The electron repulsion integrals are then approximated as
+/// (ab|cd) ≈ sum_Q B^Q_ab B^Q_cd.
+///
+/// Fails if the Coulomb metric is not positive definite, i.e., if the auxiliary basis is
+/// (numerically) linearly dependent.
+pub fn density_fitting(
+    system: &MolecularSystem,
+    auxiliary: &MolecularSystem,
+) -> anyhow::Result<Vec<SymmetricMatrix>> {
+    let metric = DMatrix::from(eri_two_center(auxiliary));
+    let cholesky = metric
+        .cholesky()
+        .context("the Coulomb metric of the auxiliary basis is not positive definite")?;
+
+    let n_basis = system.n_basis();
+    let pairs: Vec<_> = (0..n_basis)
+        .flat_map(|a| (a..n_basis).map(move |b| (a, b)))
+        .collect();
+    let three_center = eri_three_center(system, auxiliary);
+    let mut fitted = DMatrix::from_fn(three_center.len(), pairs.len(), |p, pair| {
+        three_center[p][pairs[pair]]
+    });
+    drop(three_center);
+    cholesky.l().solve_lower_triangular_mut(&mut fitted);
+
+    Ok(fitted
+        .row_iter()
+        .map(|row| {
+            let mut output = SymmetricMatrix::zeros(n_basis);
+            for (&pair, &value) in pairs.iter().zip(row.iter()) {
+                output[pair] = value;
+            }
+            output
+        })
+        .collect())
+}
+
+/// The pivots of a column block are used as long as their remaining diagonal is at least this
+/// fraction of the largest one when the block was computed
+const CHOLESKY_SPAN: f64 = 1e-2;
+
+/// Returns the vectors L^P of a pivoted incomplete Cholesky decomposition of the electron
+/// repulsion integrals of the given [MolecularSystem], seen as a matrix with the rows (ab) and
+/// columns (cd), as one matrix over the basis per vector. The integrals are approximated as
+/// (ab|cd) ≈ sum_P L^P_ab L^P_cd with an error of at most the given (positive) threshold, without
+/// an auxiliary basis as in [density_fitting].
+///
+/// Only the diagonal (ab|ab) and the columns of the chosen pivots (cd) are computed. The columns
+/// of all pairs of a shell pair are computed at once, and all of its pivots whose remaining
+/// diagonal is close to the largest one are used.
+///
+/// # References
+///
+/// [1] Aquilante, F.; Pedersen, T. B.; Lindh, R. Low-cost evaluation of the exchange Fock matrix
+/// from Cholesky and density fitting representations of the electron repulsion integrals. J.
+/// Chem. Phys. 2007, 126, 194106
+pub fn eri_cholesky(system: &MolecularSystem, threshold: f64) -> Vec<SymmetricMatrix> {
+    let start = Instant::now();
+    let hermite_cache = HermiteCache::new(system);
+    let shell_pairs = shell_pairs(system);
+
+    // the pairs of basis functions of each shell pair, with a <= b within a shell, and the index
+    // of the first pair of each shell pair among all pairs
+    let local_pairs = shell_pairs
+        .iter()
+        .map(|&(a, b)| {
+            let [basis_a, basis_b] = [a, b].map(|shell| system.shell_basis(shell));
+            (0..basis_a.matrix_count)
+                .cartesian_product(0..basis_b.matrix_count)
+                .filter(|&(i, j)| a != b || i <= j)
+                .collect_vec()
+        })
+        .collect_vec();
+    let offsets = local_pairs
+        .iter()
+        .scan(0, |offset, pairs| {
+            let start = *offset;
+            *offset += pairs.len();
+            Some(start)
+        })
+        .collect_vec();
+    let n_pairs = local_pairs.iter().map(Vec::len).sum();
+
+    // the integrals between the pairs of basis functions of two shell pairs
+    let block = |ab: usize, cd: usize| {
+        let (a, b) = shell_pairs[ab];
+        let (c, d) = shell_pairs[cd];
+        let quartet = [a, b, c, d].map(|shell| system.shell_basis(shell));
+        let [basis_a, basis_b, basis_c, basis_d] = quartet;
+        let result = eri::compute_eri(basis_a, basis_b, basis_c, basis_d, [&hermite_cache; 2]);
+        let result = spherical::transform_quartet(result, quartet.each_ref());
+        DMatrix::from_fn(
+            local_pairs[ab].len(),
+            local_pairs[cd].len(),
+            |row, column| {
+                let (i, j) = local_pairs[ab][row];
+                let (k, l) = local_pairs[cd][column];
+                result[(i, j, k, l)]
+            },
+        )
+    };
+
+    let indices = (0..shell_pairs.len()).collect_vec();
+    let diagonal_blocks = parallel::map(&indices, |&ab| block(ab, ab));
+    let shell_norms = diagonal_blocks
+        .iter()
+        .map(|block| block.max().max(0.0).sqrt())
+        .collect_vec();
+    let mut diagonal = diagonal_blocks
+        .iter()
+        .flat_map(|block| block.diagonal().iter().copied().collect_vec())
+        .collect_vec();
+    let screening_threshold = threshold.min(screening::SUFFICIENTLY_SMALL_THRESHOLD);
+
+    let mut vectors: Vec<DVector<f64>> = Vec::new();
+    while vectors.len() < n_pairs {
+        let (pivot, largest) = largest_element(&diagonal);
+        if largest <= threshold {
+            break;
+        }
+
+        // the columns of all pairs of the shell pair of the pivot
+        let cd = offsets.partition_point(|&offset| offset <= pivot) - 1;
+        let blocks = parallel::map(&indices, |&ab| {
+            (shell_norms[ab] * shell_norms[cd] >= screening_threshold).then(|| block(ab, cd))
+        });
+        let mut columns = DMatrix::zeros(n_pairs, local_pairs[cd].len());
+        for (ab, block) in blocks.into_iter().enumerate() {
+            if let Some(block) = block {
+                columns
+                    .rows_mut(offsets[ab], block.nrows())
+                    .copy_from(&block);
+            }
+        }
+
+        let limit = threshold.max(CHOLESKY_SPAN * largest);
+        loop {
+            let (column, value) = largest_element(&diagonal[offsets[cd]..][..columns.ncols()]);
+            if value <= limit {
+                break;
+            }
+            let pivot = offsets[cd] + column;
+
+            let mut vector = columns.column(column).into_owned();
+            for previous in &vectors {
+                vector.axpy(-previous[pivot], previous, 1.0);
+            }
+            vector /= value.sqrt();
+            for (diagonal, value) in diagonal.iter_mut().zip(vector.iter()) {
+                *diagonal -= value * value;
+            }
+            diagonal[pivot] = 0.0;
+            vectors.push(vector);
         }
     }
-    let diagonal_duration = start.elapsed();
-    let prediction = diagonal_duration * n_shells.pow(2) as u32 / 8;
-    println!("done precomputing diagonal for screening. took {diagonal_duration:3.3?}. Full is thus estimated to take {prediction:3.3?}");
 
-    const SUFFICIENTLY_SMALL_THRESHOLD: f64 = 1e-6;
+    log::debug!(
+        "Cholesky decomposition with {} vectors for {n_pairs} pairs took {:3.3?}",
+        vectors.len(),
+        start.elapsed()
+    );
+
+    let pairs = shell_pairs
+        .iter()
+        .zip(&local_pairs)
+        .flat_map(|(&(a, b), local_pairs)| {
+            let [basis_a, basis_b] = [a, b].map(|shell| system.shell_basis(shell));
+            local_pairs.iter().map(move |&(i, j)| {
+                (
+                    basis_a.matrix_start_index + i,
+                    basis_b.matrix_start_index + j,
+                )
+            })
+        })
+        .collect_vec();
+    vectors
+        .iter()
+        .map(|vector| {
+            let mut output = SymmetricMatrix::zeros(system.n_basis());
+            for (&pair, &value) in pairs.iter().zip(vector.iter()) {
+                output[pair] = value;
+            }
+            output
+        })
+        .collect()
+}
+
+/// Returns the index and value of the largest of the given values.
+fn largest_element(values: &[f64]) -> (usize, f64) {
+    values
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap_or((0, 0.0))
+}
 
-    let mut screened = 0;
-    let mut total = 0;
-    for a in 0..n_shells {
-        for b in a..n_shells {
-            let norm_ab = shell_norms[(a, b)];
+/// Computes the Coulomb matrix J_ij = sum_kl (ij|kl) D_kl and the exchange matrix
+/// K_ij = sum_kl (ik|jl) D_kl for the given density matrix D, without storing the
+/// electron-electron repulsion integrals. For a closed-shell system with total density D, the
+/// two-electron part of the Fock matrix is J - K / 2.
+///
+/// Shell quartets are skipped if their Schwarz bound, weighted with the largest element of the
+/// density matrix they are contracted with, is negligible.
+pub fn fock_jk(
+    system: &MolecularSystem,
+    density: &SymmetricMatrix,
+) -> (SymmetricMatrix, SymmetricMatrix) {
+    let start = Instant::now();
+    let hermite_cache = HermiteCache::new(system);
+    let shell_norms = shell_norms(system, &hermite_cache);
 
-            for c in 0..n_shells {
-                for d in c..n_shells {
-                    if (a, b) == (c, d) {
-                        // skip diagonal entries as they are precomputed for screening
-                        continue;
-                    }
+    // largest absolute density matrix element per pair of shells
+    let mut density_norms = SymmetricMatrix::zeros(system.n_shells());
+    for (a, b) in shell_pairs(system) {
+        let basis_a = system.shell_basis(a);
+        let basis_b = system.shell_basis(b);
+        for i in basis_a.matrix_start_index..basis_a.matrix_start_index + basis_a.matrix_count {
+            for j in basis_b.matrix_start_index..basis_b.matrix_start_index + basis_b.matrix_count {
+                density_norms[(a, b)] = f64::max(density_norms[(a, b)], density[(i, j)].abs());
+            }
+        }
+    }
+
+    let mut accumulator = JkAccumulator::new(density);
+    let (screened, total) = for_each_screened_quartet(
+        system,
+        |[a, b, c, d]| {
+            let density_norm = [
+                4.0 * density_norms[(a, b)],
+                4.0 * density_norms[(c, d)],
+                density_norms[(a, c)],
+                density_norms[(a, d)],
+                density_norms[(b, c)],
+                density_norms[(b, d)],
+            ]
+            .into_iter()
+            .fold(0.0, f64::max);
+
+            shell_norms[(a, b)] * shell_norms[(c, d)] * density_norm
+                < screening::SUFFICIENTLY_SMALL_THRESHOLD
+        },
+        |quartet| {
+            let [basis_a, basis_b, basis_c, basis_d] = quartet;
+            let result = eri::compute_eri(basis_a, basis_b, basis_c, basis_d, [&hermite_cache; 2]);
+            spherical::transform_quartet(result, quartet.each_ref())
+        },
+        |[basis_a, basis_b, basis_c, basis_d], result| {
+            let same_ab = basis_a.matrix_start_index == basis_b.matrix_start_index;
+            let same_cd = basis_c.matrix_start_index == basis_d.matrix_start_index;
+            let same_abcd = basis_a.matrix_start_index == basis_c.matrix_start_index
+                && basis_b.matrix_start_index == basis_d.matrix_start_index;
+
+            for ((i, j, k, l), &value) in result.indexed_iter() {
+                let index @ (i, j, k, l) = (
+                    basis_a.matrix_start_index + i,
+                    basis_b.matrix_start_index + j,
+                    basis_c.matrix_start_index + k,
+                    basis_d.matrix_start_index + l,
+                );
+
+                // within quartets of equal shells, only one of the equivalent integrals is used
+                if (same_ab && i > j) || (same_cd && k > l) || (same_abcd && (i, j) > (k, l)) {
+                    continue;
+                }
+                accumulator.add(index, value);
+            }
+        },
+    );
+
+    log::debug!(
+        "screened {screened} out of {total} shell quartets, building J and K took {:3.3?}",
+        start.elapsed()
+    );
+    (accumulator.coulomb(), accumulator.exchange())
+}
+
+/// Computes the diagonal shell quartets (ab|ab) and returns their [screening::shell_norm]s, which
+/// are used for Schwarz screening.
+fn shell_norms(system: &MolecularSystem, hermite_cache: &HermiteCache) -> SymmetricMatrix {
+    let shell_pairs = shell_pairs(system);
+    let norms = parallel::map(&shell_pairs, |&(a, b)| {
+        let basis_a = system.shell_basis(a);
+        let basis_b = system.shell_basis(b);
 
-                    let norm_cd = shell_norms[(c, d)];
-                    let norm_abcd = norm_ab * norm_cd;
+        let result = eri::compute_eri(basis_a, basis_b, basis_a, basis_b, [hermite_cache; 2]);
+        let result = spherical::transform_quartet(result, [&basis_a, &basis_b, &basis_a, &basis_b]);
 
-                    total += 1;
-                    if norm_abcd < SUFFICIENTLY_SMALL_THRESHOLD {
-                        screened += 1;
+        screening::shell_norm(&result)
+    });
+
+    let mut shell_norms = SymmetricMatrix::zeros(system.n_shells());
+    for ((a, b), norm) in shell_pairs.into_iter().zip(norms) {
+        shell_norms[(a, b)] = norm;
+    }
+    shell_norms
+}
+
+/// Returns the s function with exponent 0 and coefficient 1, i.e., the constant 1, which is paired
+/// with auxiliary basis functions to compute two- and three-center integrals with the four-center
+/// routines.
+fn unit_function() -> ContractedGaussian {
+    ContractedGaussian {
+        coefficients: smallvec![1.0],
+        exponents: smallvec![0.0],
+        angular: [0, 0, 0],
+    }
+}
+
+/// Returns a shell that consists of the given [unit_function] at the center of the given shell.
+/// Its index 0 refers to the expansion coefficients of [HermiteCache::with_unit].
+fn unit_shell<'a>(shell: &ShellBasis, unit: &'a [&'a ContractedGaussian]) -> ShellBasis<'a> {
+    ShellBasis {
+        shell_type: ShellType(0),
+        function_type: FunctionType::Cartesian,
+        atom_index: shell.atom_index,
+        center: shell.center,
+        basis: unit,
+        start_index: 0,
+        count: 1,
+        matrix_start_index: 0,
+        matrix_count: 1,
+    }
+}
+
+/// Returns all pairs of shells (a, b) with a <= b.
+fn shell_pairs(system: &MolecularSystem) -> Vec<(usize, usize)> {
+    let n_shells = system.n_shells();
+    (0..n_shells)
+        .flat_map(|a| (a..n_shells).map(move |b| (a, b)))
+        .collect()
+}
+
+/// The number of shell pairs (a, b) whose quartets are computed at once before their results are
+/// stored, which bounds the memory of the results held at the same time
+const PAIRS_PER_BATCH: usize = 64;
+
+/// Evaluates `compute` for all shell quartets (ab|cd) with a <= b, c <= d and (a, b) <= (c, d)
+/// that are not negligible, and passes the results to `store`. The remaining quartets are
+/// equivalent by symmetry.
+///
+/// With the `parallel` feature, the quartets of different pairs (a, b) are computed concurrently
+/// in batches of [PAIRS_PER_BATCH] pairs. `store` is always called in the order of the quartets
+/// for both the serial and the parallel path, so results that are summed up in `store` are
+/// reproducible and identical between the two.
+///
+/// Returns the number of screened quartets and the total number of quartets.
+fn for_each_screened_quartet<R: Send>(
+    system: &MolecularSystem,
+    is_negligible: impl Fn([usize; 4]) -> bool + Send + Sync,
+    compute: impl Fn([ShellBasis; 4]) -> R + Send + Sync,
+    mut store: impl FnMut([ShellBasis; 4], R),
+) -> (usize, usize) {
+    let n_shells = system.n_shells();
+    let screened = AtomicUsize::new(0);
+    let total = AtomicUsize::new(0);
+
+    for batch in shell_pairs(system).chunks(PAIRS_PER_BATCH) {
+        let results = parallel::map(batch, |&(a, b)| {
+            let mut results = Vec::new();
+            for c in a..n_shells {
+                let first_d = if c == a { b } else { c };
+                for d in first_d..n_shells {
+                    total.fetch_add(1, Ordering::Relaxed);
+                    if is_negligible([a, b, c, d]) {
+                        screened.fetch_add(1, Ordering::Relaxed);
                         continue;
                     }
 
-                    let basis_a @ ShellBasis {
-                        start_index: start_a,
-                        count: count_a,
-                        ..
-                    } = system.shell_basis(a);
-                    let basis_b @ ShellBasis {
-                        start_index: start_b,
-                        count: count_b,
-                        ..
-                    } = system.shell_basis(b);
-                    let basis_c @ ShellBasis {
-                        start_index: start_c,
-                        count: count_c,
-                        ..
-                    } = system.shell_basis(c);
-                    let basis_d @ ShellBasis {
-                        start_index: start_d,
-                        count: count_d,
-                        ..
-                    } = system.shell_basis(d);
-
-                    let result =
-                        eri::compute_eri(basis_a, basis_b, basis_c, basis_d, &hermite_cache);
-
-                    output.copy_from(
-                        &result,
-                        (start_a, start_b, start_c, start_d),
-                        (count_a, count_b, count_c, count_d),
+                    let quartet = [a, b, c, d].map(|shell| system.shell_basis(shell));
+                    results.push((quartet, compute(quartet)));
+                }
+            }
+            results
+        });
+
+        for (quartet, result) in results.into_iter().flatten() {
+            store(quartet, result);
+        }
+    }
+
+    (screened.into_inner(), total.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use nalgebra::{DMatrix, Point3};
+
+    use crate::{
+        basis::BasisSet,
+        storage::SymmetricMatrix,
+        system::{Atom, CubeGrid, MolecularSystem, NuclearModel, PointCharge},
+    };
+
+    #[test]
+    fn overlap_sign_convention() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let overlap = super::overlap(&system);
+
+        // the first hydrogen lies in positive x and z direction of the oxygen, whose p functions
+        // are ordered z, y, x
+        assert!(overlap[(0, 3)] > 0.0);
+        assert_eq!(overlap[(0, 4)], 0.0);
+        assert!(overlap[(0, 5)] > 0.0);
+    }
+
+    #[test]
+    fn multipole_origin_shift() {
+        let basis_set = BasisSet::load("data/basis/6-31G_st_st.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+
+        let shift = Point3::new(0.3, -1.2, 0.7);
+        let overlap = super::overlap(&system);
+        let unshifted = super::multipole(&system, Point3::origin(), 2);
+        let shifted = super::multipole(&system, shift, 2);
+
+        // x, y, z, xx, xy, xz, yy, yz, zz
+        assert_eq!(unshifted.len(), 9);
+
+        // the first basis function is an s function on the first hydrogen
+        let hydrogen = system.atoms[0].position;
+        for p in 0..3 {
+            approx::assert_relative_eq!(
+                unshifted[p][(0, 0)],
+                hydrogen[p] * overlap[(0, 0)],
+                epsilon = 1e-10
+            );
+        }
+        let quadrupole_axes = [(0, 0), (0, 1), (0, 2), (1, 1), (1, 2), (2, 2)];
+
+        let n = system.n_basis();
+        for a in 0..n {
+            for b in 0..n {
+                for p in 0..3 {
+                    let expected = unshifted[p][(a, b)] - shift[p] * overlap[(a, b)];
+                    approx::assert_relative_eq!(shifted[p][(a, b)], expected, epsilon = 1e-10);
+                }
+
+                // (r_p - O_p)(r_q - O_q) = r_p r_q - O_p r_q - O_q r_p + O_p O_q
+                for (index, &(p, q)) in quadrupole_axes.iter().enumerate() {
+                    let expected = unshifted[3 + index][(a, b)]
+                        - shift[p] * unshifted[q][(a, b)]
+                        - shift[q] * unshifted[p][(a, b)]
+                        + shift[p] * shift[q] * overlap[(a, b)];
+                    approx::assert_relative_eq!(
+                        shifted[3 + index][(a, b)],
+                        expected,
+                        epsilon = 1e-10
                     );
                 }
             }
         }
     }
 
-    println!(
-        "screened {screened} out of {total} shell quartets ({:3.1}%)",
-        screened as f32 / total as f32 * 100.0
-    );
-    println!("computing full ERI tensor took {:3.3?}", start.elapsed());
-    output
+    /// Compares analytic derivatives of a one-electron integral to central finite differences
+    fn assert_derivatives(
+        basis_path: &str,
+        integral: fn(&MolecularSystem) -> SymmetricMatrix,
+        derivatives: fn(&MolecularSystem) -> Vec<[SymmetricMatrix; 3]>,
+    ) {
+        const STEP: f64 = 1e-4;
+
+        let basis_set = BasisSet::load(basis_path).unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let analytic = derivatives(&system);
+
+        let n = system.n_basis();
+        for atom_index in 0..system.atoms.len() {
+            for (axis, analytic) in analytic[atom_index].iter().enumerate() {
+                let displaced = |step: f64| {
+                    let mut atoms = system.atoms.clone();
+                    atoms[atom_index].position[axis] += step;
+                    integral(&MolecularSystem::from_atoms(&atoms, &basis_set))
+                };
+                let forward = displaced(STEP);
+                let backward = displaced(-STEP);
+
+                for i in 0..n {
+                    for j in i..n {
+                        let numeric = (forward[(i, j)] - backward[(i, j)]) / (2.0 * STEP);
+                        approx::assert_abs_diff_eq!(analytic[(i, j)], numeric, epsilon = 1e-6);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn overlap_derivatives() {
+        assert_derivatives(
+            "data/basis/6-31G_st_st.json",
+            super::overlap,
+            super::overlap_derivatives,
+        );
+        assert_derivatives(
+            "data/basis/def2-SV(P).json",
+            super::overlap,
+            super::overlap_derivatives,
+        );
+    }
+
+    #[test]
+    fn kinetic_derivatives() {
+        assert_derivatives(
+            "data/basis/6-31G_st_st.json",
+            super::kinetic,
+            super::kinetic_derivatives,
+        );
+    }
+
+    #[test]
+    fn nuclear_derivatives() {
+        assert_derivatives(
+            "data/basis/6-31G_st_st.json",
+            super::nuclear,
+            super::nuclear_derivatives,
+        );
+        assert_derivatives(
+            "data/basis/def2-SV(P).json",
+            super::nuclear,
+            super::nuclear_derivatives,
+        );
+    }
+
+    #[test]
+    fn potential_and_field() {
+        const STEP: f64 = 1e-4;
+
+        let basis_set = BasisSet::load("data/basis/6-31G_st.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let n = system.n_basis();
+
+        // the nuclear attraction is the potential at the nuclei, weighted by their charges
+        let positions = system.atoms.iter().map(|atom| atom.position).collect_vec();
+        let potentials = super::potential(&system, &positions);
+        let nuclear = super::nuclear(&system);
+        for i in 0..n {
+            for j in i..n {
+                let expected: f64 = system
+                    .atoms
+                    .iter()
+                    .zip(&potentials)
+                    .map(|(atom, potential)| -atom.nuclear_charge() * potential[(i, j)])
+                    .sum();
+                approx::assert_abs_diff_eq!(nuclear[(i, j)], expected, epsilon = 1e-10);
+            }
+        }
+
+        let point = Point3::new(0.7, -1.1, 0.4);
+        let field = super::electric_field(&system, &[point]).remove(0);
+        for (axis, field) in field.iter().enumerate() {
+            let mut shifted = [point; 2];
+            shifted[0][axis] += STEP;
+            shifted[1][axis] -= STEP;
+            let potentials = super::potential(&system, &shifted);
+
+            for i in 0..n {
+                for j in i..n {
+                    let expected = -(potentials[0][(i, j)] - potentials[1][(i, j)]) / (2.0 * STEP);
+                    approx::assert_abs_diff_eq!(field[(i, j)], expected, epsilon = 1e-7);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn grid_values() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/hydrogen.json", &basis_set).unwrap();
+        let rhf = crate::scf::rhf(&system, &Default::default()).unwrap();
+
+        // the density integrates to the number of electrons
+        let grid = CubeGrid::around(&system, 0.2, 6.0);
+        let points = grid.points();
+        let density = super::electron_density(&system, &rhf.density, &points);
+        let n_electrons = density.iter().sum::<f64>() * grid.cell_volume();
+        approx::assert_abs_diff_eq!(n_electrons, 2.0, epsilon = 1e-4);
+
+        // and is the sum of the squares of the occupied orbitals
+        let orbitals = super::orbital_values(&system, &rhf.coefficients, &points);
+        for (density, orbitals) in density.iter().zip(orbitals.row_iter()).step_by(97) {
+            let expected = 2.0 * orbitals.columns(0, rhf.n_occupied).norm_squared();
+            approx::assert_abs_diff_eq!(density, &expected, epsilon = 1e-12);
+        }
+
+        let points = [Point3::new(0.3, -1.2, 0.8), Point3::new(-2.0, 0.1, 4.0)];
+        let esp = super::electrostatic_potential(&system, &rhf.density, &points);
+        let potentials = super::potential(&system, &points);
+        for ((point, esp), potential) in points.iter().zip(esp).zip(potentials) {
+            let nuclear: f64 = system
+                .atoms
+                .iter()
+                .map(|atom| atom.nuclear_charge() / (atom.position - point).norm())
+                .sum();
+            let electronic = DMatrix::from(&rhf.density).dot(&DMatrix::from(&potential));
+            approx::assert_abs_diff_eq!(esp, nuclear - electronic, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn gaussian_electrostatic_potential() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let mut system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let rhf = crate::scf::rhf(&system, &Default::default()).unwrap();
+
+        let oxygen = system.atoms[1];
+        let points = [oxygen.position, Point3::new(0.3, -1.2, 0.8)];
+        let point_esp = super::electrostatic_potential(&system, &rhf.density, &points);
+        system.nuclear_model = NuclearModel::Gaussian;
+        let gaussian_esp = super::electrostatic_potential(&system, &rhf.density, &points);
+
+        // the potential of a Gaussian nucleus at its center is 2 Z sqrt(ζ / π)
+        assert!(point_esp[0].is_infinite());
+        let zeta = system.nuclear_model.exponent(&oxygen).unwrap();
+        let potential = DMatrix::from(&super::potential(&system, &points[..1])[0]);
+        let others: f64 = [0, 2]
+            .into_iter()
+            .map(|i| system.nuclear_charge(i) / (system.atoms[i].position - oxygen.position).norm())
+            .sum();
+        let expected = 2.0 * oxygen.nuclear_charge() * (zeta / std::f64::consts::PI).sqrt()
+            + others
+            - DMatrix::from(&rhf.density).dot(&potential);
+        approx::assert_relative_eq!(gaussian_esp[0], expected, max_relative = 1e-12);
+
+        // away from the nuclei, the models agree
+        approx::assert_abs_diff_eq!(gaussian_esp[1], point_esp[1], epsilon = 1e-12);
+    }
+
+    #[test]
+    fn basis_derivatives() {
+        const STEP: f64 = 1e-4;
+
+        let basis_set = BasisSet::load("data/basis/6-31G_st.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let points = [Point3::new(0.3, -0.8, 0.5), Point3::new(-1.2, 0.9, 1.7)];
+
+        let derivatives = super::basis_derivatives(&system, &points, 2);
+        assert_eq!(derivatives.len(), 10);
+        approx::assert_abs_diff_eq!(
+            derivatives[0],
+            super::basis_values(&system, &points),
+            epsilon = 1e-14
+        );
+
+        // compare the gradient and the Hessian to finite differences of the lower orders
+        for axis in 0..3 {
+            let mut shifted = points.to_vec();
+            shifted.iter_mut().for_each(|point| point[axis] += STEP);
+            let forward = super::basis_derivatives(&system, &shifted, 1);
+            shifted
+                .iter_mut()
+                .for_each(|point| point[axis] -= 2.0 * STEP);
+            let backward = super::basis_derivatives(&system, &shifted, 1);
+
+            let difference =
+                |component: usize| (&forward[component] - &backward[component]) / (2.0 * STEP);
+            approx::assert_abs_diff_eq!(derivatives[1 + axis], difference(0), epsilon = 1e-7);
+            for other in 0..3 {
+                // index of the Hessian component (axis, other) in xx, xy, xz, yy, yz, zz
+                let (i, j) = (axis.min(other), axis.max(other));
+                let index = 4 + [0, 3, 5][i] + j - i;
+                approx::assert_abs_diff_eq!(
+                    derivatives[index],
+                    difference(1 + other),
+                    epsilon = 1e-6
+                );
+            }
+        }
+
+        // all shells are screened far away from the molecule
+        let far = super::basis_derivatives(&system, &[Point3::new(0.0, 0.0, 40.0)], 2);
+        assert!(far
+            .iter()
+            .all(|component| component.iter().all(|&value| value == 0.0)));
+    }
+
+    #[test]
+    fn external_charges() {
+        const STEP: f64 = 1e-4;
+
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let mut system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let point_charge = PointCharge {
+            charge: -0.8,
+            position: Point3::new(1.5, -2.0, 0.5),
+        };
+        system.external_charges.push(point_charge);
+
+        // a single external charge acts like a bare point charge in the atoms
+        let mut atoms = system.atoms.clone();
+        atoms.push(Atom::point_charge(
+            point_charge.charge,
+            point_charge.position,
+        ));
+        let reference = MolecularSystem::from_atoms(&atoms, &basis_set);
+
+        let nuclear = super::nuclear(&system);
+        let expected = super::nuclear(&reference);
+        let n = system.n_basis();
+        for i in 0..n {
+            for j in i..n {
+                approx::assert_abs_diff_eq!(nuclear[(i, j)], expected[(i, j)], epsilon = 1e-12);
+            }
+        }
+        approx::assert_abs_diff_eq!(
+            system.nuclear_repulsion_energy(),
+            reference.nuclear_repulsion_energy(),
+            epsilon = 1e-12
+        );
+
+        let analytic = super::nuclear_derivatives(&system);
+        assert_eq!(analytic.len(), system.atoms.len());
+        let atom_index = 1;
+        for (axis, analytic) in analytic[atom_index].iter().enumerate() {
+            let displaced = |step: f64| {
+                let mut displaced = MolecularSystem::from_atoms(&system.atoms, &basis_set);
+                displaced.atoms[atom_index].position[axis] += step;
+                displaced.external_charges = system.external_charges.clone();
+                super::nuclear(&displaced)
+            };
+            let forward = displaced(STEP);
+            let backward = displaced(-STEP);
+
+            for i in 0..n {
+                for j in i..n {
+                    let numeric = (forward[(i, j)] - backward[(i, j)]) / (2.0 * STEP);
+                    approx::assert_abs_diff_eq!(analytic[(i, j)], numeric, epsilon = 1e-6);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gaussian_nuclear_model() {
+        const STEP: f64 = 1e-4;
+
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let mut system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let point = super::nuclear(&system);
+        system.nuclear_model = NuclearModel::Gaussian;
+        let gaussian = super::nuclear(&system);
+
+        // the finite nuclei attract the electrons slightly less, mostly in the oxygen core
+        let n = system.n_basis();
+        let oxygen_core = system.shell_basis(1).matrix_start_index;
+        for i in 0..n {
+            let difference = gaussian[(i, i)] - point[(i, i)];
+            assert!((0.0..1e-4).contains(&difference));
+        }
+        assert!(gaussian[(oxygen_core, oxygen_core)] - point[(oxygen_core, oxygen_core)] > 1e-8);
+
+        let analytic = super::nuclear_derivatives(&system);
+        let atom_index = 1;
+        for (axis, analytic) in analytic[atom_index].iter().enumerate() {
+            let displaced = |step: f64| {
+                let mut displaced = MolecularSystem::from_atoms(&system.atoms, &basis_set);
+                displaced.atoms[atom_index].position[axis] += step;
+                displaced.nuclear_model = NuclearModel::Gaussian;
+                super::nuclear(&displaced)
+            };
+            let forward = displaced(STEP);
+            let backward = displaced(-STEP);
+
+            for i in 0..n {
+                for j in i..n {
+                    let numeric = (forward[(i, j)] - backward[(i, j)]) / (2.0 * STEP);
+                    approx::assert_abs_diff_eq!(analytic[(i, j)], numeric, epsilon = 1e-6);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn eri_derivatives() {
+        const STEP: f64 = 1e-4;
+
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let analytic = super::eri_derivatives(&system);
+
+        // the oxygen, so that quartets with all combinations of centers contribute
+        let atom_index = 1;
+        let n = system.n_basis();
+        for axis in 0..3 {
+            let displaced = |step: f64| {
+                let mut atoms = system.atoms.clone();
+                atoms[atom_index].position[axis] += step;
+                super::eri(&MolecularSystem::from_atoms(&atoms, &basis_set))
+            };
+            let forward = displaced(STEP);
+            let backward = displaced(-STEP);
+
+            for index in (0..n)
+                .flat_map(|i| (0..n).map(move |j| (i, j)))
+                .flat_map(|(i, j)| (0..n).flat_map(move |k| (0..n).map(move |l| (i, j, k, l))))
+            {
+                let numeric = (forward[index] - backward[index]) / (2.0 * STEP);
+                approx::assert_abs_diff_eq!(
+                    analytic.derivative(index, atom_index)[axis],
+                    numeric,
+                    epsilon = 1e-6
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fock_jk() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let n = system.n_basis();
+
+        // an arbitrary symmetric density
+        let density = nalgebra::DMatrix::from_fn(n, n, |i, j| 0.3 * f64::cos((i + j) as f64));
+        let density = SymmetricMatrix::from(&density);
+
+        let (coulomb, exchange) = super::fock_jk(&system, &density);
+
+        // the contributions are summed up in the same order in every run
+        let (repeated_coulomb, repeated_exchange) = super::fock_jk(&system, &density);
+        assert_eq!(DMatrix::from(&coulomb), DMatrix::from(&repeated_coulomb));
+        assert_eq!(DMatrix::from(&exchange), DMatrix::from(&repeated_exchange));
+
+        let eri = super::eri(&system);
+        for i in 0..n {
+            for j in 0..n {
+                let mut expected_coulomb = 0.0;
+                let mut expected_exchange = 0.0;
+                for k in 0..n {
+                    for l in 0..n {
+                        expected_coulomb += eri[(i, j, k, l)] * density[(k, l)];
+                        expected_exchange += eri[(i, k, j, l)] * density[(k, l)];
+                    }
+                }
+                approx::assert_abs_diff_eq!(coulomb[(i, j)], expected_coulomb, epsilon = 1e-8);
+                approx::assert_abs_diff_eq!(exchange[(i, j)], expected_exchange, epsilon = 1e-8);
+            }
+        }
+    }
+
+    #[test]
+    fn density_fitting() {
+        let basis_set = BasisSet::load("data/basis/6-31G.json").unwrap();
+        let auxiliary_set =
+            BasisSet::load("data/test/generated-even-tempered-auxiliary.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let auxiliary = MolecularSystem::from_atoms(&system.atoms, &auxiliary_set);
+
+        let metric = super::eri_two_center(&auxiliary);
+        let three_center = super::eri_three_center(&system, &auxiliary);
+        assert_eq!(three_center.len(), auxiliary.n_basis());
+
+        // s functions on the same center, for which (p|q) = 2 π^(5/2) / (pq sqrt(p + q)) with the
+        // product of a and b acting as a single Gaussian with exponent a + b
+        let s_functions = |system: &MolecularSystem| {
+            (0..system.n_shells())
+                .map(|shell| system.shell_basis(shell))
+                .filter(|shell| shell.shell_type.0 == 0 && shell.atom_index == 0)
+                .map(|shell| {
+                    (
+                        shell.matrix_start_index,
+                        shell.basis[0].iter().collect_vec(),
+                    )
+                })
+                .collect_vec()
+        };
+        let coulomb = |terms_p: &[(f64, f64)], terms_q: &[(f64, f64)]| -> f64 {
+            terms_p
+                .iter()
+                .cartesian_product(terms_q)
+                .map(|(&(c_p, p), &(c_q, q))| {
+                    c_p * c_q * 2.0 * std::f64::consts::PI.powf(2.5) / (p * q * (p + q).sqrt())
+                })
+                .sum()
+        };
+        let auxiliary_s = s_functions(&auxiliary);
+        let orbital_s = s_functions(&system);
+        for (p, function_p) in &auxiliary_s {
+            for (q, function_q) in &auxiliary_s {
+                let expected = coulomb(function_p, function_q);
+                approx::assert_relative_eq!(metric[(*p, *q)], expected, max_relative = 1e-12);
+            }
+            for ((a, function_a), (b, function_b)) in orbital_s.iter().tuple_combinations() {
+                let terms_ab = function_a
+                    .iter()
+                    .cartesian_product(function_b)
+                    .map(|(&(c_a, a), &(c_b, b))| (c_a * c_b, a + b))
+                    .collect_vec();
+                let expected = coulomb(function_p, &terms_ab);
+                approx::assert_relative_eq!(
+                    three_center[*p][(*a, *b)],
+                    expected,
+                    max_relative = 1e-12
+                );
+            }
+        }
+
+        // the fitted integrals approximate the exact ones, especially their Coulomb energy
+        let (max_error, energy, fitted_energy) = fitting_errors(&system, &auxiliary);
+        assert!(max_error < 2e-4);
+        // fitting in the Coulomb metric never overestimates the Coulomb energy
+        assert!(fitted_energy <= energy);
+        approx::assert_relative_eq!(fitted_energy, energy, max_relative = 1e-3);
+    }
+
+    #[test]
+    fn density_fitting_published_basis() {
+        // a published basis set as read from the Basis Set Exchange, with contracted, diffuse
+        // and cartesian d functions. It is not meant for fitting, hence the larger errors.
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let auxiliary_set = BasisSet::load("data/basis/6-311++G_st_st.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let auxiliary = MolecularSystem::from_atoms(&system.atoms, &auxiliary_set);
+
+        let (max_error, energy, fitted_energy) = fitting_errors(&system, &auxiliary);
+        assert!(max_error < 0.1);
+        assert!(fitted_energy <= energy);
+        approx::assert_relative_eq!(fitted_energy, energy, max_relative = 0.05);
+    }
+
+    /// Returns the largest error of the fitted (ab|cd) compared to [super::eri], and the exact and
+    /// the fitted Coulomb energy of a made-up density.
+    fn fitting_errors(system: &MolecularSystem, auxiliary: &MolecularSystem) -> (f64, f64, f64) {
+        let fitted = super::density_fitting(system, auxiliary).unwrap();
+        let eri = super::eri(system);
+        let n = system.n_basis();
+        let density = DMatrix::from_fn(n, n, |i, j| 0.3 * f64::cos((i + j) as f64));
+        let mut max_error: f64 = 0.0;
+        let mut energy = 0.0;
+        let mut fitted_energy = 0.0;
+        for (i, j, k, l) in (0..4)
+            .map(|_| 0..n)
+            .multi_cartesian_product()
+            .map(|index| (index[0], index[1], index[2], index[3]))
+        {
+            let approximation: f64 = fitted.iter().map(|b| b[(i, j)] * b[(k, l)]).sum();
+            max_error = max_error.max((approximation - eri[(i, j, k, l)]).abs());
+            energy += density[(i, j)] * eri[(i, j, k, l)] * density[(k, l)];
+            fitted_energy += density[(i, j)] * approximation * density[(k, l)];
+        }
+        (max_error, energy, fitted_energy)
+    }
+
+    #[test]
+    fn eri_cholesky() {
+        let basis_set = BasisSet::load("data/basis/6-31G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let eri = super::eri(&system);
+        let n = system.n_basis();
+
+        let mut previous_count = 0;
+        for threshold in [1e-4, 1e-8] {
+            let vectors = super::eri_cholesky(&system, threshold);
+            let vectors = vectors.iter().map(DMatrix::from).collect_vec();
+            for (i, j) in (0..n).cartesian_product(0..n) {
+                for (k, l) in (0..n).cartesian_product(0..n) {
+                    let approximation: f64 =
+                        vectors.iter().map(|l_p| l_p[(i, j)] * l_p[(k, l)]).sum();
+                    approx::assert_abs_diff_eq!(
+                        approximation,
+                        eri[(i, j, k, l)],
+                        epsilon = threshold
+                    );
+                }
+            }
+
+            // tighter thresholds need more vectors, but fewer than there are pairs
+            assert!(vectors.len() > previous_count);
+            assert!(vectors.len() < n * (n + 1) / 2);
+            previous_count = vectors.len();
+        }
+    }
 }
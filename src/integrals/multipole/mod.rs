@@ -0,0 +1,67 @@
+use nalgebra::{DMatrix, Point3};
+
+use crate::system::ShellBasis;
+
+use super::utils::hermite_expansion;
+
+/// Function to compute the cartesian multipole moment integrals <a|(x - C_x)^i (y - C_y)^j (z - C_z)^k|b>
+/// between two electron shells of arbitrary type, for all given components [i, j, k] and an origin C.
+pub(crate) fn compute_multipole(
+    ShellBasis {
+        center: pos_a,
+        basis: basis_a,
+        count: count_a,
+        ..
+    }: ShellBasis,
+    ShellBasis {
+        center: pos_b,
+        basis: basis_b,
+        count: count_b,
+        ..
+    }: ShellBasis,
+    origin: Point3<f64>,
+    components: &[[i32; 3]],
+) -> Vec<DMatrix<f64>> {
+    let diff = pos_a - pos_b;
+    let diff_origin = pos_b - origin;
+
+    let mut result = vec![DMatrix::zeros(count_a, count_b); components.len()];
+
+    for (i, a) in basis_a.iter().enumerate() {
+        for (j, b) in basis_b.iter().enumerate() {
+            for (coeff_a, exp_a) in a.iter() {
+                for (coeff_b, exp_b) in b.iter() {
+                    let prefactor =
+                        coeff_a * coeff_b * (std::f64::consts::PI / (exp_a + exp_b)).powi(3).sqrt();
+
+                    // (x - C_x)^e = ((x - B_x) + (B_x - C_x))^e, so the moment integral is a sum of
+                    // overlap integrals with increased angular momentum on b.
+                    let moment = |axis: usize, exponent: i32| -> f64 {
+                        (0..=exponent)
+                            .map(|k| {
+                                binomial(exponent, k)
+                                    * diff_origin[axis].powi(exponent - k)
+                                    * hermite_expansion(
+                                        [a.angular[axis], b.angular[axis] + k, 0],
+                                        diff[axis],
+                                        exp_a,
+                                        exp_b,
+                                    )
+                            })
+                            .sum()
+                    };
+
+                    for (output, &[ex, ey, ez]) in result.iter_mut().zip(components) {
+                        output[(i, j)] += prefactor * moment(0, ex) * moment(1, ey) * moment(2, ez);
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
+fn binomial(n: i32, k: i32) -> f64 {
+    (1..=k).map(|i| (n - k + i) as f64 / i as f64).product()
+}
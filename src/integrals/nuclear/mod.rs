@@ -1,8 +1,9 @@
-use nalgebra::{DMatrix, Vector3};
+use itertools::Itertools;
+use nalgebra::{DMatrix, Point3, Vector3};
 
-use crate::system::{Atom, MolecularSystem, ShellBasis};
+use crate::system::{MolecularSystem, ShellBasis};
 
-use super::utils::{coulomb_auxiliary, hermite_expansion, product_center};
+use super::utils::{center_derivative, coulomb_auxiliary, hermite_expansion, product_center};
 
 pub(crate) fn compute_nuclear(
     basis_a @ ShellBasis {
@@ -36,7 +37,7 @@ fn gen_nuclear(
     }: ShellBasis,
     system: &MolecularSystem,
 ) -> DMatrix<f64> {
-    let diff = pos_b - pos_a;
+    let diff = pos_a - pos_b;
 
     let mut result = DMatrix::zeros(count_a, count_b);
 
@@ -57,11 +58,16 @@ fn gen_nuclear(
                 for (coeff_b, exp_b) in b.iter() {
                     let product_center = product_center(exp_a, pos_a, exp_b, pos_b);
 
-                    for atom in &system.atoms {
-                        let diff_nucl = atom.position - product_center;
+                    // ghost atoms don't contribute
+                    for (charge, position, exponent) in system
+                        .attraction_centers()
+                        .filter(|&(charge, _, _)| charge != 0.0)
+                    {
+                        let diff_nucl = product_center - position;
 
-                        sum += single_atom(
-                            atom,
+                        sum += single_center(
+                            charge,
+                            exponent,
                             angular_a,
                             angular_b,
                             [coeff_a, coeff_b],
@@ -80,8 +86,156 @@ fn gen_nuclear(
     result
 }
 
-fn single_atom(
-    atom: &Atom,
+/// Function to compute the derivatives <∂a/∂A_i|V_C|b> of the electron-nuclear attraction
+/// integrals between two electron shells with respect to the center A of the first shell, for all
+/// three directions i, separately for the attraction V_C to each atom C of the system, followed by
+/// the attraction to each external point charge. In contrast to [compute_nuclear], the full blocks
+/// are computed.
+pub(crate) fn compute_nuclear_derivative(
+    ShellBasis {
+        center: pos_a,
+        basis: basis_a,
+        count: count_a,
+        ..
+    }: ShellBasis,
+    ShellBasis {
+        center: pos_b,
+        basis: basis_b,
+        count: count_b,
+        ..
+    }: ShellBasis,
+    system: &MolecularSystem,
+) -> Vec<[DMatrix<f64>; 3]> {
+    let diff = pos_a - pos_b;
+
+    let centers = system.attraction_centers().collect_vec();
+    let mut result = vec![[(); 3].map(|_| DMatrix::zeros(count_a, count_b)); centers.len()];
+
+    for (i, a) in basis_a.iter().enumerate() {
+        for (j, b) in basis_b.iter().enumerate() {
+            for (coeff_a, exp_a) in a.iter() {
+                for (coeff_b, exp_b) in b.iter() {
+                    let product_center = product_center(exp_a, pos_a, exp_b, pos_b);
+
+                    for (&(charge, position, exponent), result) in
+                        centers.iter().zip(result.iter_mut())
+                    {
+                        let diff_nucl = product_center - position;
+
+                        for (axis, result) in result.iter_mut().enumerate() {
+                            result[(i, j)] +=
+                                center_derivative(a.angular, exp_a, axis, |angular_a| {
+                                    single_center(
+                                        charge,
+                                        exponent,
+                                        angular_a,
+                                        b.angular,
+                                        [coeff_a, coeff_b],
+                                        [exp_a, exp_b],
+                                        diff,
+                                        diff_nucl,
+                                    )
+                                });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Function to compute the potential integrals <a|1/|r - C||b> between two electron shells at each
+/// of the given points C, or, if `field` is set, the electric field integrals
+/// <a|(C - r)_i/|r - C|^3|b> for all three directions i at each point. The full blocks are
+/// computed.
+pub(crate) fn compute_point_integrals(
+    ShellBasis {
+        center: pos_a,
+        basis: basis_a,
+        count: count_a,
+        ..
+    }: ShellBasis,
+    ShellBasis {
+        center: pos_b,
+        basis: basis_b,
+        count: count_b,
+        ..
+    }: ShellBasis,
+    points: &[Point3<f64>],
+    field: bool,
+) -> Vec<DMatrix<f64>> {
+    let diff = pos_a - pos_b;
+    let n_components = if field { 3 } else { 1 };
+    let mut result = vec![DMatrix::zeros(count_a, count_b); n_components * points.len()];
+
+    for (i, a) in basis_a.iter().enumerate() {
+        for (j, b) in basis_b.iter().enumerate() {
+            for (coeff_a, exp_a) in a.iter() {
+                for (coeff_b, exp_b) in b.iter() {
+                    let product_center = product_center(exp_a, pos_a, exp_b, pos_b);
+
+                    for (point, result) in points.iter().zip(result.chunks_mut(n_components)) {
+                        let values = point_values(
+                            a.angular,
+                            b.angular,
+                            [exp_a, exp_b],
+                            diff,
+                            product_center - point,
+                        );
+                        let values = if field { &values[1..] } else { &values[..1] };
+                        for (result, value) in result.iter_mut().zip(values) {
+                            result[(i, j)] += coeff_a * coeff_b * value;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Returns the potential integral <a|1/|r - C||b> of a pair of primitives at the point C,
+/// followed by the electric field integrals, i.e., its negative derivatives with respect to C,
+/// which amount to raising the Hermite index along the respective direction.
+fn point_values(
+    [l1, m1, n1]: [i32; 3],
+    [l2, m2, n2]: [i32; 3],
+    [exp_a, exp_b]: [f64; 2],
+    diff: Vector3<f64>,
+    diff_point: Vector3<f64>,
+) -> [f64; 4] {
+    let p = exp_a + exp_b;
+
+    let mut values = [0.0; 4];
+    for t in 0..=l1 + l2 {
+        for u in 0..=m1 + m2 {
+            for v in 0..=n1 + n2 {
+                let e1 = hermite_expansion([l1, l2, t], diff.x, exp_a, exp_b);
+                let e2 = hermite_expansion([m1, m2, u], diff.y, exp_a, exp_b);
+                let e3 = hermite_expansion([n1, n2, v], diff.z, exp_a, exp_b);
+                let e = e1 * e2 * e3;
+
+                values[0] += e * coulomb_auxiliary(t, u, v, 0, p, diff_point);
+                values[1] += e * coulomb_auxiliary(t + 1, u, v, 0, p, diff_point);
+                values[2] += e * coulomb_auxiliary(t, u + 1, v, 0, p, diff_point);
+                values[3] += e * coulomb_auxiliary(t, u, v + 1, 0, p, diff_point);
+            }
+        }
+    }
+
+    values.map(|value| value * std::f64::consts::TAU / p)
+}
+
+/// Returns the attraction integral of a pair of primitives to a single point charge, or to a
+/// Gaussian charge distribution with the given exponent ζ. The potential erf(sqrt(ζ) r) / r of the
+/// latter amounts to the reduced exponent pζ / (p + ζ) in the auxiliary integrals.
+#[allow(clippy::too_many_arguments)]
+fn single_center(
+    charge: f64,
+    exponent: Option<f64>,
     [l1, m1, n1]: [i32; 3],
     [l2, m2, n2]: [i32; 3],
     [coeff_a, coeff_b]: [f64; 2],
@@ -90,7 +244,10 @@ fn single_atom(
     diff_nucl: Vector3<f64>,
 ) -> f64 {
     let p = exp_a + exp_b;
-    let nuclear_charge = atom.ordinal as f64;
+    let (reduced, scale) = match exponent {
+        None => (p, 1.0),
+        Some(zeta) => (p * zeta / (p + zeta), (zeta / (p + zeta)).sqrt()),
+    };
 
     let mut atom_sum = 0.0;
     for t in 0..=l1 + l2 {
@@ -100,10 +257,10 @@ fn single_atom(
                 let e2 = hermite_expansion([m1, m2, u], diff.y, exp_a, exp_b);
                 let e3 = hermite_expansion([n1, n2, v], diff.z, exp_a, exp_b);
 
-                atom_sum += e1 * e2 * e3 * coulomb_auxiliary(t, u, v, 0, p, diff_nucl);
+                atom_sum += e1 * e2 * e3 * coulomb_auxiliary(t, u, v, 0, reduced, diff_nucl);
             }
         }
     }
 
-    coeff_a * coeff_b * atom_sum * (-nuclear_charge * std::f64::consts::TAU / p)
+    coeff_a * coeff_b * atom_sum * scale * (-charge * std::f64::consts::TAU / p)
 }
@@ -2,7 +2,7 @@ use nalgebra::DMatrix;
 
 use crate::system::ShellBasis;
 
-use super::utils::hermite_expansion;
+use super::utils::{center_derivative, hermite_expansion, primitive_overlap};
 
 /// Function to compute the overlap integrals between two electron shells of arbitrary type
 pub(crate) fn compute_overlap(
@@ -36,7 +36,7 @@ fn gen_overlap(
         ..
     }: ShellBasis,
 ) -> DMatrix<f64> {
-    let diff = pos_b - pos_a;
+    let diff = pos_a - pos_b;
 
     // Use a matrix to organize results. result[(i, j)] = S_ij
     let mut result = DMatrix::zeros(count_a, count_b);
@@ -78,3 +78,45 @@ fn gen_overlap(
     }
     result
 }
+
+/// Function to compute the derivatives <∂a/∂A_i|b> of the overlap integrals between two electron
+/// shells with respect to the center A of the first shell, for all three directions i.
+/// In contrast to [compute_overlap], the full block is computed.
+pub(crate) fn compute_overlap_derivative(
+    ShellBasis {
+        center: pos_a,
+        basis: basis_a,
+        count: count_a,
+        ..
+    }: ShellBasis,
+    ShellBasis {
+        center: pos_b,
+        basis: basis_b,
+        count: count_b,
+        ..
+    }: ShellBasis,
+) -> [DMatrix<f64>; 3] {
+    let diff = pos_a - pos_b;
+
+    let mut result = [(); 3].map(|_| DMatrix::zeros(count_a, count_b));
+
+    for (i, a) in basis_a.iter().enumerate() {
+        for (j, b) in basis_b.iter().enumerate() {
+            let [l2, m2, n2] = b.angular;
+
+            for (coeff_a, exp_a) in a.iter() {
+                for (coeff_b, exp_b) in b.iter() {
+                    for (axis, result) in result.iter_mut().enumerate() {
+                        result[(i, j)] += coeff_a
+                            * coeff_b
+                            * center_derivative(a.angular, exp_a, axis, |[l1, m1, n1]| {
+                                primitive_overlap(exp_a, exp_b, (l1, m1, n1), (l2, m2, n2), diff)
+                            });
+                    }
+                }
+            }
+        }
+    }
+
+    result
+}
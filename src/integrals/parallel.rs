@@ -0,0 +1,14 @@
+//! Helpers that distribute work over the rayon thread pool if the `parallel` feature is enabled,
+//! and fall back to serial iteration otherwise.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Applies the given function to every item and collects the results in the order of the items.
+pub(super) fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Send + Sync) -> Vec<R> {
+    #[cfg(feature = "parallel")]
+    return items.par_iter().map(f).collect();
+
+    #[cfg(not(feature = "parallel"))]
+    items.iter().map(f).collect()
+}
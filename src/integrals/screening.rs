@@ -1,5 +1,8 @@
 use ndarray::Array4;
 
+/// Shell quartets whose Schwarz bound is below this threshold are not computed.
+pub const SUFFICIENTLY_SMALL_THRESHOLD: f64 = 1e-6;
+
 pub fn shell_norm(result: &Array4<f64>) -> f64 {
     let mut norm = 0.0;
 
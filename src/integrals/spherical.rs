@@ -0,0 +1,202 @@
+//! Transformation of integrals over the cartesian components of a shell into integrals over real
+//! solid harmonics.
+
+use nalgebra::DMatrix;
+use ndarray::Array4;
+
+use crate::{basis::FunctionType, system::ShellBasis};
+
+/// Returns the coefficient of the normalized cartesian gaussian with the given angular vector in
+/// the normalized real solid harmonic gaussian with quantum numbers l and m. Negative m select the
+/// "sine"-like, positive m the "cosine"-like real solid harmonics.
+///
+/// # References
+///
+/// [1] Schlegel, H. B.; Frisch, M. J. Transformation between Cartesian and pure spherical harmonic
+///     Gaussians. Int. J. Quantum Chem. 1995, 54, 83–87, eq. (15)
+fn coefficient(l: i32, m: i32, [lx, ly, lz]: [i32; 3]) -> f64 {
+    let abs_m = m.abs();
+    if (lx + ly - abs_m) % 2 != 0 {
+        return 0.0;
+    }
+
+    let j = (lx + ly - abs_m) / 2;
+    if j < 0 {
+        return 0.0;
+    }
+
+    // cosine-like components only contain even powers of y, sine-like ones only odd powers
+    let i = abs_m - lx;
+    let component = if m >= 0 { 1.0 } else { -1.0 };
+    if component != parity(i.abs()) {
+        return 0.0;
+    }
+
+    let mut prefactor = f64::sqrt(
+        factorial(2 * lx) * factorial(2 * ly) * factorial(2 * lz) / factorial(2 * l)
+            * factorial(l - abs_m)
+            / factorial(l)
+            / factorial(l + abs_m)
+            / (factorial(lx) * factorial(ly) * factorial(lz)),
+    ) / 2.0f64.powi(l);
+    prefactor *= if m < 0 {
+        parity((i - 1) / 2)
+    } else {
+        parity(i / 2)
+    };
+
+    let mut sum = 0.0;
+    for i in j..=(l - abs_m) / 2 {
+        let inner: f64 = (((lx - abs_m) / 2).max(0)..=j.min(lx / 2))
+            .filter(|&k| lx - 2 * k <= abs_m)
+            .map(|k| binomial(j, k) * binomial(abs_m, lx - 2 * k) * parity(k))
+            .sum();
+
+        sum += binomial(l, i) * binomial(i, j) * parity(i) * factorial(2 * (l - i))
+            / factorial(l - abs_m - 2 * i)
+            * inner;
+    }
+
+    if m == 0 {
+        prefactor * sum
+    } else {
+        std::f64::consts::SQRT_2 * prefactor * sum
+    }
+}
+
+/// Returns the matrix that transforms the cartesian components of the given shell into the basis
+/// functions of that shell, or [None] if the shell is cartesian already.
+/// The rows of the matrix correspond to m = -l, ..., l.
+pub(super) fn transformation(shell: &ShellBasis) -> Option<DMatrix<f64>> {
+    match shell.function_type {
+        FunctionType::Cartesian => None,
+        FunctionType::Spherical => {
+            let l = shell.shell_type.0;
+            Some(DMatrix::from_fn(shell.matrix_count, shell.count, |m, i| {
+                coefficient(l, m as i32 - l, shell.basis[i].angular)
+            }))
+        }
+    }
+}
+
+/// Transforms a block of two-center integrals between the cartesian components of two shells into
+/// the basis of those shells.
+pub(super) fn transform_pair(
+    block: DMatrix<f64>,
+    shell_a: &ShellBasis,
+    shell_b: &ShellBasis,
+) -> DMatrix<f64> {
+    let block = match transformation(shell_a) {
+        Some(transformation) => transformation * block,
+        None => block,
+    };
+    match transformation(shell_b) {
+        Some(transformation) => block * transformation.transpose(),
+        None => block,
+    }
+}
+
+/// Transforms a block of four-center integrals between the cartesian components of four shells
+/// into the basis of those shells.
+pub(super) fn transform_quartet(block: Array4<f64>, shells: [&ShellBasis; 4]) -> Array4<f64> {
+    shells
+        .into_iter()
+        .enumerate()
+        .fold(block, |block, (axis, shell)| match transformation(shell) {
+            Some(transformation) => transform_axis(block, axis, &transformation),
+            None => block,
+        })
+}
+
+/// Contracts the given axis of a four-center block with the transformation matrix.
+fn transform_axis(block: Array4<f64>, axis: usize, transformation: &DMatrix<f64>) -> Array4<f64> {
+    let mut shape = [0; 4];
+    shape.copy_from_slice(block.shape());
+    shape[axis] = transformation.nrows();
+
+    Array4::from_shape_fn(shape, |(i, j, k, l)| {
+        let mut index = [i, j, k, l];
+        let m = index[axis];
+
+        (0..transformation.ncols())
+            .map(|cartesian| {
+                index[axis] = cartesian;
+                transformation[(m, cartesian)] * block[index]
+            })
+            .sum()
+    })
+}
+
+fn parity(i: i32) -> f64 {
+    if i % 2 == 0 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+fn factorial(n: i32) -> f64 {
+    (1..=n).map(f64::from).product()
+}
+
+fn binomial(n: i32, k: i32) -> f64 {
+    if k < 0 || k > n {
+        0.0
+    } else {
+        factorial(n) / (factorial(k) * factorial(n - k))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point3;
+
+    use crate::{
+        basis::BasisSet,
+        system::{Atom, MolecularSystem},
+    };
+
+    /// The spherical components of a shell on a single atom have to be orthonormal. Only holds
+    /// for uncontracted shells, as contractions aren't renormalized.
+    fn assert_spherical_shell_orthogonal(basis_path: &str, ordinal: usize, l: i32) {
+        let basis_set = BasisSet::load(basis_path).unwrap();
+        let atoms = [Atom::new(ordinal, Point3::new(0.1, -0.2, 0.3))];
+        let system = MolecularSystem::from_atoms(&atoms, &basis_set);
+        let overlap = crate::overlap(&system);
+
+        let shell = (0..system.n_shells())
+            .map(|shell| system.shell_basis(shell))
+            .find(|shell| shell.shell_type.0 == l)
+            .unwrap();
+        assert_eq!(shell.matrix_count, 2 * l as usize + 1);
+
+        let start = shell.matrix_start_index;
+        for i in start..start + shell.matrix_count {
+            for j in start..start + shell.matrix_count {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                approx::assert_relative_eq!(overlap[(i, j)], expected, epsilon = 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn spherical_d_shell() {
+        assert_spherical_shell_orthogonal("data/basis/def2-SV(P).json", 6, 2);
+    }
+
+    #[test]
+    fn spherical_f_shell() {
+        assert_spherical_shell_orthogonal("data/basis/6-31G_st_st.json", 30, 3);
+    }
+
+    #[test]
+    fn spherical_basis_size() {
+        let basis_set = BasisSet::load("data/basis/def2-SV(P).json").unwrap();
+        let atoms = [Atom::new(6, Point3::origin())];
+        let system = MolecularSystem::from_atoms(&atoms, &basis_set);
+
+        // 3s2p1d
+        assert_eq!(system.n_basis(), 3 + 2 * 3 + 5);
+        assert_eq!(system.basis.len(), 3 + 2 * 3 + 6);
+    }
+}
@@ -2,7 +2,8 @@
 
 use nalgebra::{Point3, Vector3};
 
-/// Returns the hermite expansion coefficients as commonly used in molecular integrals
+/// Returns the hermite expansion coefficients as commonly used in molecular integrals, where
+/// `dist` is the difference A - B of the centers of the two gaussians along the axis.
 ///
 /// # References
 ///
@@ -69,3 +70,72 @@ pub(super) fn product_center(
 ) -> Point3<f64> {
     Point3::from((exp_a * pos_a.coords + exp_b * pos_b.coords) / (exp_a + exp_b))
 }
+
+/// Overlap integral between two primitive gaussians, where `diff` is the difference A - B of their
+/// centers.
+pub(super) fn primitive_overlap(
+    exp_a: f64,
+    exp_b: f64,
+    (l1, m1, n1): (i32, i32, i32),
+    (l2, m2, n2): (i32, i32, i32),
+    diff: Vector3<f64>,
+) -> f64 {
+    (std::f64::consts::PI / (exp_a + exp_b)).powi(3).sqrt()
+        * hermite_expansion([l1, l2, 0], diff.x, exp_a, exp_b)
+        * hermite_expansion([m1, m2, 0], diff.y, exp_a, exp_b)
+        * hermite_expansion([n1, n2, 0], diff.z, exp_a, exp_b)
+}
+
+/// Returns the derivative of some integral over a primitive gaussian with respect to the center A
+/// of that gaussian along the given axis, given a function that evaluates the integral for
+/// arbitrary angular momenta of the gaussian. This uses
+///     ∂/∂A_i G(l) = 2a G(l + 1_i) - l_i G(l - 1_i)
+pub(super) fn center_derivative(
+    angular: [i32; 3],
+    exponent: f64,
+    axis: usize,
+    integral: impl Fn([i32; 3]) -> f64,
+) -> f64 {
+    let mut raised = angular;
+    raised[axis] += 1;
+    let mut result = 2.0 * exponent * integral(raised);
+
+    if angular[axis] > 0 {
+        let mut lowered = angular;
+        lowered[axis] -= 1;
+        result -= angular[axis] as f64 * integral(lowered);
+    }
+
+    result
+}
+
+/// Returns the nodes and weights of the Gauss-Legendre quadrature with n points on [-1, 1], which
+/// integrates polynomials up to degree 2n - 1 exactly.
+pub(super) fn gauss_legendre(n: usize) -> (Vec<f64>, Vec<f64>) {
+    let mut nodes = Vec::with_capacity(n);
+    let mut weights = Vec::with_capacity(n);
+    for i in 0..n {
+        // Newton's method for the roots of P_n, starting from an asymptotic approximation
+        let mut x = f64::cos(std::f64::consts::PI * (i as f64 + 0.75) / (n as f64 + 0.5));
+        let mut derivative = 1.0;
+        for _ in 0..100 {
+            let (mut current, mut previous) = (x, 1.0);
+            for k in 2..=n {
+                let next =
+                    ((2 * k - 1) as f64 * x * current - (k - 1) as f64 * previous) / k as f64;
+                (previous, current) = (current, next);
+            }
+
+            derivative = n as f64 * (x * current - previous) / (x * x - 1.0);
+            let step = current / derivative;
+            x -= step;
+            if step.abs() < 1e-15 {
+                break;
+            }
+        }
+
+        nodes.push(x);
+        weights.push(2.0 / ((1.0 - x * x) * derivative * derivative));
+    }
+    (nodes, weights)
+}
@@ -0,0 +1,174 @@
+use nalgebra::{DMatrix, Point3};
+use smallvec::SmallVec;
+
+use crate::{
+    basis::ContractedGaussian,
+    system::{MolecularSystem, ShellBasis},
+};
+
+use super::{multipole_components, parallel, spherical};
+
+/// The number of points that are evaluated at once
+const POINT_CHUNK_SIZE: usize = 1024;
+
+/// Values (and derivatives) of basis functions below this threshold are neglected
+const CUTOFF: f64 = 1e-14;
+
+/// Returns the exponents [i, j, k] of the derivatives ∂^i/∂x^i ∂^j/∂y^j ∂^k/∂z^k up to the given
+/// order, sorted by order and within each order as in [multipole_components].
+pub(super) fn derivative_components(order: usize) -> Vec<[i32; 3]> {
+    (0..=order).flat_map(multipole_components).collect()
+}
+
+/// Returns the distance from the center of the given shell beyond which its basis functions and
+/// their derivatives up to the given order are below [CUTOFF].
+fn shell_extent(shell: &ShellBasis, order: usize) -> f64 {
+    let order = order as i32;
+    shell
+        .basis
+        .iter()
+        .flat_map(|function| {
+            // each derivative raises the power of r by at most one and brings down at most 2a
+            let power = function.angular.iter().sum::<i32>() + order;
+            function.iter().map(move |(coefficient, exponent)| {
+                let scale = coefficient.abs() * (2.0 * exponent).max(1.0).powi(order);
+                let log_scale = (scale / CUTOFF).ln();
+
+                // solve scale * r^power * exp(-a r^2) = CUTOFF by fixed-point iteration
+                let mut radius = (log_scale.max(0.0) / exponent).sqrt().max(1.0);
+                for _ in 0..5 {
+                    radius = ((log_scale + power as f64 * radius.ln()).max(0.0) / exponent).sqrt();
+                }
+                radius
+            })
+        })
+        .fold(0.0, f64::max)
+}
+
+/// Adds the derivatives of the given (cartesian) function in the given components at the given
+/// offset from its center to the given output.
+fn add_function_derivatives(
+    function: &ContractedGaussian,
+    diff: [f64; 3],
+    components: &[[i32; 3]],
+    output: &mut [f64],
+) {
+    let order = components
+        .last()
+        .map_or(0, |component| component.iter().sum()) as usize;
+    let distance_squared: f64 = diff.iter().map(|d| d * d).sum();
+
+    for (coefficient, exponent) in function.iter() {
+        let radial = coefficient * (-exponent * distance_squared).exp();
+        if radial == 0.0 {
+            continue;
+        }
+
+        // the derivatives of d^l exp(-a d^2) along each axis without the exponential, which follow
+        // from ∂/∂d d^m = m d^(m-1) - 2a d^(m+1)
+        let derivatives = [0, 1, 2].map(|axis| {
+            let l = function.angular[axis] as usize;
+            let mut terms: SmallVec<[f64; 16]> =
+                (0..=l + order).map(|m| diff[axis].powi(m as i32)).collect();
+            let mut derivatives: SmallVec<[f64; 4]> = SmallVec::from_slice(&[terms[l]]);
+            for k in 1..=order {
+                let mut previous = 0.0;
+                for m in 0..=l + order - k {
+                    let term = m as f64 * previous - 2.0 * exponent * terms[m + 1];
+                    previous = std::mem::replace(&mut terms[m], term);
+                }
+                derivatives.push(terms[l]);
+            }
+            derivatives
+        });
+
+        for (output, [i, j, k]) in output.iter_mut().zip(components) {
+            *output += radial
+                * derivatives[0][*i as usize]
+                * derivatives[1][*j as usize]
+                * derivatives[2][*k as usize];
+        }
+    }
+}
+
+/// Returns the derivatives of the basis functions of the given shell in the given components at
+/// the given points, with one matrix (one row per point) per component. Spherical shells are
+/// transformed from their cartesian components.
+fn shell_derivatives(
+    shell: &ShellBasis,
+    points: &[Point3<f64>],
+    components: &[[i32; 3]],
+    extent: f64,
+) -> Vec<DMatrix<f64>> {
+    let mut cartesian = vec![DMatrix::zeros(points.len(), shell.count); components.len()];
+    let mut derivatives = vec![0.0; components.len()];
+    for (index, point) in points.iter().enumerate() {
+        let diff = point - shell.center;
+        if diff.norm_squared() > extent * extent {
+            continue;
+        }
+        for (i, function) in shell.basis.iter().enumerate() {
+            derivatives.fill(0.0);
+            add_function_derivatives(function, diff.into(), components, &mut derivatives);
+            for (cartesian, derivative) in cartesian.iter_mut().zip(&derivatives) {
+                cartesian[(index, i)] = *derivative;
+            }
+        }
+    }
+
+    match spherical::transformation(shell) {
+        Some(transformation) => cartesian
+            .into_iter()
+            .map(|cartesian| cartesian * transformation.transpose())
+            .collect(),
+        None => cartesian,
+    }
+}
+
+/// Returns the derivatives up to the given order of all basis functions of the given
+/// [MolecularSystem] at the given points, in the order of [derivative_components], with one row
+/// per point. Shells that are too far from all points are skipped.
+fn chunk_derivatives(
+    system: &MolecularSystem,
+    points: &[Point3<f64>],
+    order: usize,
+) -> Vec<DMatrix<f64>> {
+    let components = derivative_components(order);
+    let mut output = vec![DMatrix::zeros(points.len(), system.n_basis()); components.len()];
+    for shell in 0..system.n_shells() {
+        let shell = system.shell_basis(shell);
+        let extent = shell_extent(&shell, order);
+        if points
+            .iter()
+            .all(|point| (point - shell.center).norm_squared() > extent * extent)
+        {
+            continue;
+        }
+
+        let derivatives = shell_derivatives(&shell, points, &components, extent);
+        for (output, derivatives) in output.iter_mut().zip(derivatives) {
+            output
+                .columns_mut(shell.matrix_start_index, shell.matrix_count)
+                .copy_from(&derivatives);
+        }
+    }
+    output
+}
+
+/// Evaluates the basis functions and their derivatives up to the given order on chunks of the
+/// given points in parallel, applies the given function to the index of the first point and the
+/// results of each chunk and returns its results in the order of the chunks.
+pub(crate) fn map_chunks<R: Send>(
+    system: &MolecularSystem,
+    points: &[Point3<f64>],
+    order: usize,
+    f: impl Fn(usize, Vec<DMatrix<f64>>) -> R + Send + Sync,
+) -> Vec<R> {
+    let chunks: Vec<_> = points.chunks(POINT_CHUNK_SIZE).enumerate().collect();
+    parallel::map(&chunks, |&(index, chunk)| {
+        f(
+            index * POINT_CHUNK_SIZE,
+            chunk_derivatives(system, chunk, order),
+        )
+    })
+}
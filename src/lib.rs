@@ -1,7 +1,15 @@
 pub mod basis;
+pub mod dft;
+pub mod grid;
 mod integrals;
 mod periodic_table;
+pub mod scf;
 pub mod storage;
 pub mod system;
 
-pub use integrals::{eri, kinetic, nuclear, overlap};
+pub use integrals::{
+    basis_derivatives, basis_values, density_fitting, ecp, electric_field, electron_density,
+    electrostatic_potential, eri, eri_cholesky, eri_derivatives, eri_three_center, eri_two_center,
+    fock_jk, kinetic, kinetic_derivatives, multipole, multipole_components, nuclear,
+    nuclear_derivatives, orbital_values, overlap, overlap_derivatives, potential,
+};
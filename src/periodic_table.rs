@@ -330,6 +330,89 @@ define_atoms! {
     }
 }
 
+/// The mass numbers of the most abundant or, for radioactive elements, the most stable isotope of
+/// each element, indexed by ordinal - 1
+const MASS_NUMBERS: [usize; 118] = [
+    1, 4, 7, 9, 11, 12, 14, 16, 19, 20, 23, 24, 27, 28, 31, 32, 35, 40, 39, 40, 45, 48, 51, 52, 55,
+    56, 59, 58, 63, 64, 69, 74, 75, 80, 79, 84, 85, 88, 89, 90, 93, 98, 98, 102, 103, 106, 107,
+    114, 115, 120, 121, 130, 127, 132, 133, 138, 139, 140, 141, 142, 145, 152, 153, 158, 159, 164,
+    165, 166, 169, 174, 175, 180, 181, 184, 187, 192, 193, 195, 197, 202, 205, 208, 209, 209, 210,
+    222, 223, 226, 227, 232, 231, 238, 237, 244, 243, 247, 247, 251, 252, 257, 258, 259, 262, 267,
+    268, 269, 270, 269, 278, 281, 282, 285, 286, 289, 290, 293, 294, 294,
+];
+
+/// The Bragg-Slater radii of the elements up to radon in Ångström, indexed by ordinal - 1. The values
+/// for hydrogen and the noble gases, which Slater did not give, are the ones commonly used for
+/// molecular integration grids.
+const BRAGG_SLATER_RADII: [f64; 86] = [
+    0.35, 1.40, 1.45, 1.05, 0.85, 0.70, 0.65, 0.60, 0.50, 1.50, 1.80, 1.50, 1.25, 1.10, 1.00, 1.00,
+    1.00, 1.80, 2.20, 1.80, 1.60, 1.40, 1.35, 1.40, 1.40, 1.40, 1.35, 1.35, 1.35, 1.35, 1.30, 1.25,
+    1.15, 1.15, 1.15, 1.90, 2.35, 2.00, 1.80, 1.55, 1.45, 1.45, 1.35, 1.30, 1.35, 1.40, 1.60, 1.55,
+    1.55, 1.45, 1.45, 1.40, 1.40, 2.10, 2.60, 2.15, 1.95, 1.85, 1.85, 1.85, 1.85, 1.85, 1.85, 1.80,
+    1.75, 1.75, 1.75, 1.75, 1.75, 1.75, 1.75, 1.55, 1.45, 1.35, 1.35, 1.30, 1.35, 1.35, 1.35, 1.50,
+    1.90, 1.80, 1.60, 1.90, 1.45, 2.10,
+];
+
+/// The scaling factors ξ of the radial grids of Treutler and Ahlrichs for the elements up to
+/// krypton, indexed by ordinal - 1
+const TREUTLER_AHLRICHS_SCALES: [f64; 36] = [
+    0.8, 0.9, 1.8, 1.4, 1.3, 1.1, 0.9, 0.9, 0.9, 0.9, 1.4, 1.3, 1.3, 1.2, 1.1, 1.0, 1.0, 1.0, 1.5,
+    1.4, 1.3, 1.2, 1.2, 1.2, 1.2, 1.2, 1.2, 1.1, 1.1, 1.1, 1.1, 1.0, 0.9, 0.9, 0.9, 0.9,
+];
+
+impl ElementType {
+    /// Returns the mass number of the most abundant or, for radioactive elements, the most stable
+    /// isotope of this element.
+    pub fn mass_number(&self) -> usize {
+        MASS_NUMBERS[*self as usize - 1]
+    }
+
+    /// Returns the root-mean-square radius of the nucleus of this element in Bohr, following the
+    /// empirical formula r = (0.836 A^(1/3) + 0.570) fm for the mass number A.
+    ///
+    /// # References
+    ///
+    /// [1] Visscher, L.; Dyall, K. G. Dirac-Fock atomic electronic structure calculations using
+    /// different nuclear charge distributions. At. Data Nucl. Data Tables 1997, 67, 207–224
+    pub fn nuclear_radius(&self) -> f64 {
+        const FEMTOMETER_TO_BOHR: f64 = 1e-5 / 0.529177210903;
+        (0.836 * (self.mass_number() as f64).cbrt() + 0.570) * FEMTOMETER_TO_BOHR
+    }
+
+    /// Returns the period (row) of this element in the periodic table.
+    pub fn period(&self) -> usize {
+        const LAST_ORDINALS: [usize; 7] = [2, 10, 18, 36, 54, 86, 118];
+        1 + LAST_ORDINALS
+            .iter()
+            .position(|&last| *self as usize <= last)
+            .unwrap()
+    }
+
+    /// Returns the Bragg-Slater radius of this element in Bohr. Elements after radon use the radius
+    /// of the lanthanides.
+    ///
+    /// # References
+    ///
+    /// [1] Slater, J. C. Atomic Radii in Crystals. J. Chem. Phys. 1964, 41, 3199–3204
+    pub fn bragg_slater_radius(&self) -> f64 {
+        BRAGG_SLATER_RADII.get(*self as usize - 1).unwrap_or(&1.75)
+            * crate::system::ANGSTROM_TO_BOHR
+    }
+
+    /// Returns the scaling factor ξ of the radial grid of Treutler and Ahlrichs for this element,
+    /// which is 1 for the elements after krypton.
+    ///
+    /// # References
+    ///
+    /// [1] Treutler, O.; Ahlrichs, R. Efficient molecular numerical integration schemes. J. Chem.
+    /// Phys. 1995, 102, 346–354
+    pub fn treutler_ahlrichs_scale(&self) -> f64 {
+        *TREUTLER_AHLRICHS_SCALES
+            .get(*self as usize - 1)
+            .unwrap_or(&1.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -344,4 +427,24 @@ mod tests {
         assert!(matches!(helium, Ok(ElementType::He)));
         assert!(matches!(uranium, Ok(ElementType::U)));
     }
+
+    #[test]
+    fn nuclear_radius() {
+        assert_eq!(ElementType::H.mass_number(), 1);
+        assert_eq!(ElementType::U.mass_number(), 238);
+        assert_eq!(ElementType::Og.mass_number(), 294);
+
+        // 5.7509 fm
+        approx::assert_relative_eq!(ElementType::U.nuclear_radius(), 1.08677e-4, epsilon = 1e-8);
+    }
+
+    #[test]
+    fn period() {
+        assert_eq!(ElementType::H.period(), 1);
+        assert_eq!(ElementType::Ne.period(), 2);
+        assert_eq!(ElementType::Na.period(), 3);
+        assert_eq!(ElementType::Kr.period(), 4);
+        assert_eq!(ElementType::Cs.period(), 6);
+        assert_eq!(ElementType::Og.period(), 7);
+    }
 }
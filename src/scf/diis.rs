@@ -0,0 +1,85 @@
+use std::collections::VecDeque;
+
+use itertools::Itertools;
+use nalgebra::{DMatrix, DVector};
+
+/// Direct inversion in the iterative subspace (DIIS): extrapolates the Fock matrix from previous
+/// iterations such that the extrapolated error vector is minimal.
+///
+/// Several Fock matrices (e.g., for alpha and beta spin) can be extrapolated together, in which
+/// case the error vector consists of the errors of all of them.
+///
+/// # References
+///
+/// [1] Pulay, P. Improved SCF convergence acceleration. J. Comput. Chem. 1982, 3, 556–560
+pub(super) struct Diis {
+    capacity: usize,
+    focks: VecDeque<Vec<DMatrix<f64>>>,
+    errors: VecDeque<Vec<DMatrix<f64>>>,
+}
+
+impl Diis {
+    /// Creates a [Diis] that keeps at most `capacity` previous Fock matrices.
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            focks: VecDeque::with_capacity(capacity),
+            errors: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Stores the given Fock matrices along with their error vectors, and returns the extrapolated
+    /// Fock matrices.
+    pub(super) fn extrapolate(
+        &mut self,
+        focks: Vec<DMatrix<f64>>,
+        errors: Vec<DMatrix<f64>>,
+    ) -> Vec<DMatrix<f64>> {
+        if self.capacity == 0 {
+            return focks;
+        }
+
+        if self.focks.len() == self.capacity {
+            self.focks.pop_front();
+            self.errors.pop_front();
+        }
+        self.focks.push_back(focks);
+        self.errors.push_back(errors);
+
+        loop {
+            let n = self.focks.len();
+
+            let mut b = DMatrix::from_element(n + 1, n + 1, -1.0);
+            b[(n, n)] = 0.0;
+            for i in 0..n {
+                for j in 0..n {
+                    b[(i, j)] = self.errors[i]
+                        .iter()
+                        .zip(&self.errors[j])
+                        .map(|(a, b)| a.dot(b))
+                        .sum();
+                }
+            }
+            let mut rhs = DVector::zeros(n + 1);
+            rhs[n] = -1.0;
+
+            match b.lu().solve(&rhs) {
+                Some(weights) if weights.iter().all(|w| w.is_finite()) => {
+                    let mut extrapolated = self.focks[0].iter().map(|f| f * 0.0).collect_vec();
+                    for (focks, &weight) in self.focks.iter().zip(weights.iter()) {
+                        for (extrapolated, fock) in extrapolated.iter_mut().zip(focks) {
+                            *extrapolated += fock * weight;
+                        }
+                    }
+                    return extrapolated;
+                }
+                // the error vectors became linearly dependent, so the oldest one is dropped
+                _ if n > 1 => {
+                    self.focks.pop_front();
+                    self.errors.pop_front();
+                }
+                _ => return self.focks[0].clone(),
+            }
+        }
+    }
+}
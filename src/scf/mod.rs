@@ -0,0 +1,210 @@
+//! This module contains self-consistent field (SCF) drivers that are built on top of the integrals
+//! of this crate.
+
+mod diis;
+mod rhf;
+mod rks;
+mod rohf;
+mod uhf;
+
+use anyhow::bail;
+use itertools::Itertools;
+use nalgebra::{DMatrix, DVector, SymmetricEigen};
+
+use crate::{storage::EriTensor, storage::SymmetricMatrix, system::MolecularSystem};
+
+pub use rhf::{rhf, RhfResult};
+pub use rks::{rks, RksResult};
+pub use rohf::{rohf, RohfResult};
+pub use uhf::{uhf, UhfResult};
+
+/// How the transformation X with X^T S X = 1 is constructed from the overlap matrix S.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Orthogonalization {
+    /// X = S^(-1/2). The orthogonalized functions stay as close as possible to the original ones,
+    /// but this fails for (nearly) linearly dependent basis sets.
+    Symmetric,
+    /// X = U s^(-1/2), where eigenvectors U whose eigenvalues s are below the linear dependency
+    /// threshold are dropped.
+    Canonical,
+}
+
+/// How the two-electron contributions to the Fock matrix are computed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TwoElectronMethod {
+    /// Computes and stores the [EriTensor] once.
+    Stored,
+    /// Recomputes the integrals in every iteration using [crate::fock_jk].
+    Direct,
+}
+
+/// Options that control the SCF iterations.
+#[derive(Clone, Debug)]
+pub struct ScfOptions {
+    pub max_iterations: usize,
+    /// Convergence threshold for the change of the total energy between two iterations
+    pub energy_threshold: f64,
+    /// Convergence threshold for the largest element of the orbital gradient FDS - SDF in the
+    /// orthogonalized basis
+    pub gradient_threshold: f64,
+    pub orthogonalization: Orthogonalization,
+    /// Eigenvalues of the overlap matrix below this threshold indicate linear dependencies
+    pub linear_dependency_threshold: f64,
+    /// The number of previous Fock matrices used for DIIS extrapolation. 0 disables DIIS.
+    pub diis_size: usize,
+    pub two_electron: TwoElectronMethod,
+}
+
+impl Default for ScfOptions {
+    fn default() -> Self {
+        Self {
+            max_iterations: 100,
+            energy_threshold: 1e-9,
+            gradient_threshold: 1e-6,
+            orthogonalization: Orthogonalization::Symmetric,
+            linear_dependency_threshold: 1e-6,
+            diis_size: 8,
+            two_electron: TwoElectronMethod::Stored,
+        }
+    }
+}
+
+/// Returns the core Hamiltonian, i.e., the kinetic energy, electron-nuclear attraction and
+/// effective core potentials.
+fn core_hamiltonian(system: &MolecularSystem) -> DMatrix<f64> {
+    DMatrix::from(crate::kinetic(system))
+        + DMatrix::from(crate::nuclear(system))
+        + DMatrix::from(crate::ecp(system))
+}
+
+/// Returns the transformation X with X^T S X = 1 for the given overlap matrix S.
+fn orthogonalizer(overlap: &DMatrix<f64>, options: &ScfOptions) -> anyhow::Result<DMatrix<f64>> {
+    let SymmetricEigen {
+        eigenvalues,
+        eigenvectors,
+    } = SymmetricEigen::new(overlap.clone());
+
+    let threshold = options.linear_dependency_threshold;
+    match options.orthogonalization {
+        Orthogonalization::Symmetric => {
+            if eigenvalues.min() < threshold {
+                bail!(
+                    "the basis is nearly linearly dependent (smallest overlap eigenvalue {:e}), use canonical orthogonalization instead",
+                    eigenvalues.min()
+                );
+            }
+
+            let inverse_sqrt = DMatrix::from_diagonal(&eigenvalues.map(|s| s.sqrt().recip()));
+            Ok(&eigenvectors * inverse_sqrt * eigenvectors.transpose())
+        }
+        Orthogonalization::Canonical => {
+            let kept = (0..eigenvalues.len())
+                .filter(|&i| eigenvalues[i] >= threshold)
+                .collect_vec();
+            if kept.len() < eigenvalues.len() {
+                log::info!(
+                    "dropping {} linearly dependent combinations of basis functions",
+                    eigenvalues.len() - kept.len()
+                );
+            }
+
+            Ok(DMatrix::from_fn(overlap.nrows(), kept.len(), |i, j| {
+                eigenvectors[(i, kept[j])] / eigenvalues[kept[j]].sqrt()
+            }))
+        }
+    }
+}
+
+/// Solves the Roothaan-Hall equations FC = SCe for the given Fock matrix, using the
+/// orthogonalizing transformation X. Returns the orbital energies in ascending order along with the
+/// orbital coefficients (as columns).
+fn diagonalize(fock: &DMatrix<f64>, orthogonalizer: &DMatrix<f64>) -> (DVector<f64>, DMatrix<f64>) {
+    let transformed = orthogonalizer.transpose() * fock * orthogonalizer;
+    let SymmetricEigen {
+        eigenvalues,
+        eigenvectors,
+    } = SymmetricEigen::new(transformed);
+
+    let order = (0..eigenvalues.len())
+        .sorted_by(|&i, &j| eigenvalues[i].total_cmp(&eigenvalues[j]))
+        .collect_vec();
+    let energies = DVector::from_fn(order.len(), |i, _| eigenvalues[order[i]]);
+    let coefficients = orthogonalizer * eigenvectors.select_columns(&order);
+
+    (energies, coefficients)
+}
+
+/// Returns the density matrix sum_i C_ai C_bi over the first `n_occupied` orbitals.
+fn density(coefficients: &DMatrix<f64>, n_occupied: usize) -> DMatrix<f64> {
+    let occupied = coefficients.columns(0, n_occupied);
+    occupied * occupied.transpose()
+}
+
+/// Returns the orbital gradient X^T (FDS - SDF) X, which vanishes at convergence.
+fn orbital_gradient(
+    fock: &DMatrix<f64>,
+    density: &DMatrix<f64>,
+    overlap: &DMatrix<f64>,
+    orthogonalizer: &DMatrix<f64>,
+) -> DMatrix<f64> {
+    orthogonalizer.transpose()
+        * (fock * density * overlap - overlap * density * fock)
+        * orthogonalizer
+}
+
+/// Returns the alpha and beta Fock matrices for the given alpha and beta density matrices, along
+/// with the electronic energy.
+fn unrestricted_fock(
+    hamiltonian: &DMatrix<f64>,
+    two_electron: &TwoElectron,
+    densities: &[DMatrix<f64>; 2],
+) -> ([DMatrix<f64>; 2], f64) {
+    let [(coulomb_alpha, exchange_alpha), (coulomb_beta, exchange_beta)] = densities
+        .each_ref()
+        .map(|density| two_electron.coulomb_exchange(density));
+    let coulomb = coulomb_alpha + coulomb_beta;
+    let focks = [
+        hamiltonian + &coulomb - exchange_alpha,
+        hamiltonian + &coulomb - exchange_beta,
+    ];
+
+    let energy = densities
+        .iter()
+        .zip(&focks)
+        .map(|(density, fock)| 0.5 * density.dot(&(hamiltonian + fock)))
+        .sum();
+    (focks, energy)
+}
+
+/// The source of the two-electron contributions to the Fock matrix.
+enum TwoElectron<'a, 'b> {
+    Stored(EriTensor),
+    Direct(&'a MolecularSystem<'b>),
+}
+
+impl<'a, 'b> TwoElectron<'a, 'b> {
+    fn new(system: &'a MolecularSystem<'b>, method: TwoElectronMethod) -> Self {
+        match method {
+            TwoElectronMethod::Stored => Self::Stored(crate::eri(system)),
+            TwoElectronMethod::Direct => Self::Direct(system),
+        }
+    }
+
+    /// Returns the Coulomb and exchange matrices for the given density matrix.
+    fn coulomb_exchange(&self, density: &DMatrix<f64>) -> (DMatrix<f64>, DMatrix<f64>) {
+        let density = SymmetricMatrix::from(density);
+        let (coulomb, exchange) = match self {
+            Self::Stored(eri) => (eri.coulomb(&density), eri.exchange(&density)),
+            Self::Direct(system) => crate::fock_jk(system, &density),
+        };
+        (DMatrix::from(coulomb), DMatrix::from(exchange))
+    }
+
+    /// Returns the Coulomb matrix for the given density matrix.
+    fn coulomb(&self, density: &DMatrix<f64>) -> DMatrix<f64> {
+        match self {
+            Self::Stored(eri) => DMatrix::from(eri.coulomb(&SymmetricMatrix::from(density))),
+            Self::Direct(_) => self.coulomb_exchange(density).0,
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use anyhow::{bail, ensure};
+use nalgebra::{DMatrix, DVector};
+
+use crate::{storage::SymmetricMatrix, system::MolecularSystem};
+
+use super::{
+    core_hamiltonian, density, diagonalize, diis::Diis, orbital_gradient, orthogonalizer,
+    ScfOptions, TwoElectron,
+};
+
+/// The converged result of a restricted Hartree-Fock calculation.
+#[derive(Clone, Debug)]
+pub struct RhfResult {
+    /// The total energy, including the nuclear repulsion energy
+    pub energy: f64,
+    pub nuclear_repulsion_energy: f64,
+    /// The orbital energies in ascending order
+    pub orbital_energies: DVector<f64>,
+    /// The orbital coefficients, one orbital per column, in the order of `orbital_energies`
+    pub coefficients: DMatrix<f64>,
+    /// The total (alpha + beta) density matrix
+    pub density: SymmetricMatrix,
+    /// The number of doubly occupied orbitals
+    pub n_occupied: usize,
+    /// The number of iterations needed for convergence
+    pub iterations: usize,
+}
+
+/// Runs a restricted (closed-shell) Hartree-Fock calculation for the given [MolecularSystem],
+/// starting from the core Hamiltonian guess.
+///
+/// Fails if the system is not a singlet or the iterations don't converge.
+pub fn rhf(system: &MolecularSystem, options: &ScfOptions) -> anyhow::Result<RhfResult> {
+    let (n_alpha, n_beta) = system.electron_counts()?;
+    ensure!(
+        n_alpha == n_beta,
+        "restricted Hartree-Fock requires a closed-shell system, got multiplicity {}",
+        system.multiplicity
+    );
+    let n_occupied = n_alpha;
+    let n_electrons = n_alpha + n_beta;
+
+    let overlap = DMatrix::from(crate::overlap(system));
+    let hamiltonian = core_hamiltonian(system);
+    let orthogonalizer = orthogonalizer(&overlap, options)?;
+    ensure!(
+        n_occupied <= orthogonalizer.ncols(),
+        "{n_electrons} electrons don't fit into {} orbitals",
+        orthogonalizer.ncols()
+    );
+
+    let two_electron = TwoElectron::new(system, options.two_electron);
+    let nuclear_repulsion_energy = system.nuclear_repulsion_energy();
+
+    let (_, coefficients) = diagonalize(&hamiltonian, &orthogonalizer);
+    let mut density = 2.0 * density(&coefficients, n_occupied);
+
+    let mut diis = Diis::new(options.diis_size);
+    let mut previous_energy = f64::NAN;
+    for iteration in 1..=options.max_iterations {
+        let (coulomb, exchange) = two_electron.coulomb_exchange(&density);
+        let fock = &hamiltonian + coulomb - 0.5 * exchange;
+
+        let energy = 0.5 * density.dot(&(&hamiltonian + &fock)) + nuclear_repulsion_energy;
+        let error = orbital_gradient(&fock, &density, &overlap, &orthogonalizer);
+        let gradient = error.amax();
+
+        log::debug!("RHF iteration {iteration}: energy {energy:.10}, gradient {gradient:.3e}");
+
+        if (energy - previous_energy).abs() < options.energy_threshold
+            && gradient < options.gradient_threshold
+        {
+            let (orbital_energies, coefficients) = diagonalize(&fock, &orthogonalizer);
+            return Ok(RhfResult {
+                energy,
+                nuclear_repulsion_energy,
+                orbital_energies,
+                coefficients,
+                density: SymmetricMatrix::from(&density),
+                n_occupied,
+                iterations: iteration,
+            });
+        }
+        previous_energy = energy;
+
+        let fock = diis.extrapolate(vec![fock], vec![error]).remove(0);
+        let (_, coefficients) = diagonalize(&fock, &orthogonalizer);
+        density = 2.0 * super::density(&coefficients, n_occupied);
+    }
+
+    bail!(
+        "RHF did not converge within {} iterations",
+        options.max_iterations
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point3;
+
+    use crate::{
+        basis::BasisSet,
+        scf::{Orthogonalization, ScfOptions, TwoElectronMethod},
+        system::{Atom, MolecularSystem, ANGSTROM_TO_BOHR},
+    };
+
+    #[test]
+    fn hydrogen_molecule() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/hydrogen.json", &basis_set).unwrap();
+        let result = super::rhf(&system, &ScfOptions::default()).unwrap();
+
+        // Szabo, Ostlund: Modern Quantum Chemistry, section 3.5.2
+        approx::assert_abs_diff_eq!(result.energy, -1.1167, epsilon = 1e-4);
+        approx::assert_abs_diff_eq!(result.orbital_energies[0], -0.578, epsilon = 1e-3);
+        approx::assert_abs_diff_eq!(result.orbital_energies[1], 0.670, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn water() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let atoms = [
+            (8, [0.0, -0.143225816552, 0.0]),
+            (1, [1.638036840407, 1.136548822547, 0.0]),
+            (1, [-1.638036840407, 1.136548822547, 0.0]),
+        ]
+        .map(|(ordinal, position)| Atom::new(ordinal, Point3::from(position)));
+        let system = MolecularSystem::from_atoms(&atoms, &basis_set);
+
+        let options = ScfOptions::default();
+        let stored = super::rhf(&system, &options).unwrap();
+
+        // reference values from T. D. Crawford's programming projects, project #3
+        approx::assert_abs_diff_eq!(
+            stored.nuclear_repulsion_energy,
+            8.00236706181045,
+            epsilon = 1e-8
+        );
+        approx::assert_abs_diff_eq!(stored.energy, -74.942079928192, epsilon = 1e-6);
+
+        let options = ScfOptions {
+            orthogonalization: Orthogonalization::Canonical,
+            two_electron: TwoElectronMethod::Direct,
+            ..Default::default()
+        };
+        let direct = super::rhf(&system, &options).unwrap();
+        approx::assert_abs_diff_eq!(direct.energy, stored.energy, epsilon = 1e-8);
+    }
+
+    #[test]
+    fn water_input_file() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let result = super::rhf(&system, &ScfOptions::default()).unwrap();
+
+        // the geometries in data/mol are not published ones, so there is no reference energy for
+        // them, and this regression value only guards against changes
+        approx::assert_abs_diff_eq!(result.energy, -73.8337946887, epsilon = 1e-7);
+    }
+
+    #[test]
+    fn benzene_input_file() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/benzene.json", &basis_set).unwrap();
+
+        // the nuclear repulsion energy of the coordinates in the file, computed independently
+        assert_eq!(system.n_basis(), 36);
+        assert_eq!(system.electron_counts().unwrap(), (21, 21));
+        approx::assert_abs_diff_eq!(
+            system.nuclear_repulsion_energy(),
+            326.58484662452,
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    #[cfg_attr(debug_assertions, ignore = "slow in debug builds")]
+    fn benzene() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+
+        // the HF/STO-3G equilibrium geometry with C-C 1.387 Å and C-H 1.083 Å
+        let atoms: Vec<_> = (0..12)
+            .map(|i| {
+                let angle = (i % 6) as f64 * std::f64::consts::FRAC_PI_3;
+                let (ordinal, radius) = if i < 6 {
+                    (6, 1.387)
+                } else {
+                    (1, 1.387 + 1.083)
+                };
+                let radius = radius * ANGSTROM_TO_BOHR;
+                Atom::new(
+                    ordinal,
+                    Point3::new(radius * angle.cos(), radius * angle.sin(), 0.0),
+                )
+            })
+            .collect();
+        let system = MolecularSystem::from_atoms(&atoms, &basis_set);
+        let result = super::rhf(&system, &ScfOptions::default()).unwrap();
+
+        // reference value from the NIST Computational Chemistry Comparison and Benchmark Database
+        approx::assert_abs_diff_eq!(result.energy, -227.8913, epsilon = 1e-4);
+
+        let system = MolecularSystem::load("data/mol/benzene.json", &basis_set).unwrap();
+        let result = super::rhf(&system, &ScfOptions::default()).unwrap();
+
+        // there is no reference energy for the geometry in data/mol, which is not a published
+        // one, so this regression value only guards against changes
+        approx::assert_abs_diff_eq!(result.energy, -218.5858952183, epsilon = 1e-7);
+    }
+
+    #[test]
+    fn effective_core_potential() {
+        let basis_set = BasisSet::load("data/basis/def2-SV(P).json").unwrap();
+        let atoms = [Atom::new(37, Point3::origin())];
+        let mut system = MolecularSystem::from_atoms(&atoms, &basis_set);
+        system.charge = 1;
+        system.multiplicity = 1;
+
+        // the 28 core electrons of rubidium are replaced by the potential
+        assert_eq!(system.nuclear_charge(0), 9.0);
+        assert_eq!(system.electron_counts().unwrap(), (4, 4));
+        let result = super::rhf(&system, &ScfOptions::default()).unwrap();
+
+        // regression value
+        approx::assert_abs_diff_eq!(result.energy, -23.6625414581, epsilon = 1e-7);
+    }
+
+    #[test]
+    fn open_shell() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let mut system = MolecularSystem::load("data/mol/hydrogen.json", &basis_set).unwrap();
+        system.charge = 1;
+        assert!(super::rhf(&system, &ScfOptions::default()).is_err());
+
+        system.charge = 0;
+        system.multiplicity = 3;
+        assert!(super::rhf(&system, &ScfOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,223 @@
+use anyhow::{bail, ensure};
+use nalgebra::{DMatrix, DVector};
+
+use crate::{
+    dft::Functional,
+    grid::{GridOptions, MolecularGrid},
+    storage::SymmetricMatrix,
+    system::MolecularSystem,
+};
+
+use super::{
+    core_hamiltonian, density, diagonalize, diis::Diis, orbital_gradient, orthogonalizer,
+    ScfOptions, TwoElectron,
+};
+
+/// The converged result of a restricted Kohn-Sham calculation.
+#[derive(Clone, Debug)]
+pub struct RksResult {
+    /// The total energy, including the nuclear repulsion energy
+    pub energy: f64,
+    pub nuclear_repulsion_energy: f64,
+    /// The exchange-correlation energy of the functional, without the exact exchange of hybrids
+    pub exchange_correlation_energy: f64,
+    /// The orbital energies in ascending order
+    pub orbital_energies: DVector<f64>,
+    /// The orbital coefficients, one orbital per column, in the order of `orbital_energies`
+    pub coefficients: DMatrix<f64>,
+    /// The total (alpha + beta) density matrix
+    pub density: SymmetricMatrix,
+    /// The number of doubly occupied orbitals
+    pub n_occupied: usize,
+    /// The number of iterations needed for convergence
+    pub iterations: usize,
+}
+
+/// Runs a restricted (closed-shell) Kohn-Sham calculation with the given exchange-correlation
+/// functional for the given [MolecularSystem], starting from the core Hamiltonian guess. The
+/// functional is integrated on a [MolecularGrid] with the given options, and hybrid functionals
+/// add their fraction of exact exchange to the Kohn-Sham matrix.
+///
+/// Fails if the system is not a singlet, the grid options are invalid or the iterations don't
+/// converge.
+pub fn rks(
+    system: &MolecularSystem,
+    functional: Functional,
+    options: &ScfOptions,
+    grid_options: &GridOptions,
+) -> anyhow::Result<RksResult> {
+    let (n_alpha, n_beta) = system.electron_counts()?;
+    ensure!(
+        n_alpha == n_beta,
+        "restricted Kohn-Sham requires a closed-shell system, got multiplicity {}",
+        system.multiplicity
+    );
+    let n_occupied = n_alpha;
+    let n_electrons = n_alpha + n_beta;
+
+    let overlap = DMatrix::from(crate::overlap(system));
+    let hamiltonian = core_hamiltonian(system);
+    let orthogonalizer = orthogonalizer(&overlap, options)?;
+    ensure!(
+        n_occupied <= orthogonalizer.ncols(),
+        "{n_electrons} electrons don't fit into {} orbitals",
+        orthogonalizer.ncols()
+    );
+
+    let grid = MolecularGrid::new(system, grid_options)?;
+    let two_electron = TwoElectron::new(system, options.two_electron);
+    let nuclear_repulsion_energy = system.nuclear_repulsion_energy();
+    let exact_exchange = functional.exact_exchange();
+
+    let (_, coefficients) = diagonalize(&hamiltonian, &orthogonalizer);
+    let mut density = 2.0 * density(&coefficients, n_occupied);
+
+    let mut diis = Diis::new(options.diis_size);
+    let mut previous_energy = f64::NAN;
+    for iteration in 1..=options.max_iterations {
+        // the energy of the Fock matrix without exchange-correlation potential is 1/2 D (h + F)
+        let mut fock = &hamiltonian + two_electron_part(&two_electron, &density, exact_exchange);
+        let (exchange_correlation_energy, potential) = crate::dft::exchange_correlation(
+            system,
+            &grid,
+            functional,
+            &SymmetricMatrix::from(&density),
+        );
+
+        let energy = 0.5 * density.dot(&(&hamiltonian + &fock))
+            + exchange_correlation_energy
+            + nuclear_repulsion_energy;
+        fock += DMatrix::from(potential);
+        let error = orbital_gradient(&fock, &density, &overlap, &orthogonalizer);
+        let gradient = error.amax();
+
+        log::debug!("RKS iteration {iteration}: energy {energy:.10}, gradient {gradient:.3e}");
+
+        if (energy - previous_energy).abs() < options.energy_threshold
+            && gradient < options.gradient_threshold
+        {
+            let (orbital_energies, coefficients) = diagonalize(&fock, &orthogonalizer);
+            return Ok(RksResult {
+                energy,
+                nuclear_repulsion_energy,
+                exchange_correlation_energy,
+                orbital_energies,
+                coefficients,
+                density: SymmetricMatrix::from(&density),
+                n_occupied,
+                iterations: iteration,
+            });
+        }
+        previous_energy = energy;
+
+        let fock = diis.extrapolate(vec![fock], vec![error]).remove(0);
+        let (_, coefficients) = diagonalize(&fock, &orthogonalizer);
+        density = 2.0 * super::density(&coefficients, n_occupied);
+    }
+
+    bail!(
+        "RKS did not converge within {} iterations",
+        options.max_iterations
+    )
+}
+
+/// Returns the Coulomb matrix and the given fraction of the exchange matrix for the given density
+/// matrix, where the exchange matrix is only computed if needed.
+fn two_electron_part(
+    two_electron: &TwoElectron,
+    density: &DMatrix<f64>,
+    exact_exchange: f64,
+) -> DMatrix<f64> {
+    if exact_exchange == 0.0 {
+        return two_electron.coulomb(density);
+    }
+    let (coulomb, exchange) = two_electron.coulomb_exchange(density);
+    coulomb - 0.5 * exact_exchange * exchange
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point3;
+
+    use crate::{
+        basis::BasisSet,
+        dft::Functional,
+        grid::GridOptions,
+        scf::{ScfOptions, TwoElectronMethod},
+        system::{Atom, MolecularSystem, ANGSTROM_TO_BOHR},
+    };
+
+    #[test]
+    fn helium() {
+        let basis_set = BasisSet::load("data/basis/6-311G.json").unwrap();
+        let system = MolecularSystem::from_atoms(&[Atom::new(2, Point3::origin())], &basis_set);
+        let options = ScfOptions::default();
+        let result = super::rks(&system, Functional::Svwn5, &options, &Default::default()).unwrap();
+
+        // the LDA basis set limit of the total energy and the orbital energy, which this basis
+        // without polarization functions approaches from above
+        approx::assert_abs_diff_eq!(result.energy, -2.8348, epsilon = 3e-3);
+        assert!(result.energy > -2.8348);
+        approx::assert_abs_diff_eq!(result.orbital_energies[0], -0.5704, epsilon = 6e-3);
+    }
+
+    #[test]
+    fn water_b3lyp() {
+        let basis_set = BasisSet::load("data/basis/6-31G_st.json").unwrap();
+
+        // the B3LYP/6-31G* equilibrium geometry with O-H 0.969 Å and H-O-H 103.6°
+        let (distance, half_angle) = (0.969 * ANGSTROM_TO_BOHR, 51.8f64.to_radians());
+        let (x, z) = (distance * half_angle.sin(), distance * half_angle.cos());
+        let atoms = [(8, [0.0, 0.0, 0.0]), (1, [x, 0.0, z]), (1, [-x, 0.0, z])]
+            .map(|(ordinal, position)| Atom::new(ordinal, Point3::from(position)));
+        let system = MolecularSystem::from_atoms(&atoms, &basis_set);
+        let grid_options = GridOptions {
+            radial_points: 40,
+            angular_points: 194,
+            ..Default::default()
+        };
+        let result = super::rks(
+            &system,
+            Functional::B3lyp,
+            &ScfOptions::default(),
+            &grid_options,
+        )
+        .unwrap();
+
+        // reference value from the NIST Computational Chemistry Comparison and Benchmark Database,
+        // computed with Gaussian, whose B3LYP uses the RPA parametrization of VWN
+        approx::assert_abs_diff_eq!(result.energy, -76.408953, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn water() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let grid_options = GridOptions {
+            radial_points: 30,
+            angular_points: 110,
+            ..Default::default()
+        };
+
+        // regression values
+        for (functional, expected) in [
+            (Functional::Pbe, -74.075329939),
+            (Functional::B3lyp, -74.166140429),
+        ] {
+            let result =
+                super::rks(&system, functional, &ScfOptions::default(), &grid_options).unwrap();
+            approx::assert_abs_diff_eq!(result.energy, expected, epsilon = 1e-7);
+        }
+
+        let options = ScfOptions {
+            two_electron: TwoElectronMethod::Direct,
+            ..Default::default()
+        };
+        let direct = super::rks(&system, Functional::Pbe0, &options, &grid_options).unwrap();
+        approx::assert_abs_diff_eq!(direct.energy, -74.104953572, epsilon = 1e-7);
+
+        let mut system = system;
+        system.multiplicity = 3;
+        assert!(super::rks(&system, Functional::Pbe, &options, &grid_options).is_err());
+    }
+}
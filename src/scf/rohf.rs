@@ -0,0 +1,219 @@
+use anyhow::{bail, ensure};
+use nalgebra::{DMatrix, DVector};
+
+use crate::{storage::SymmetricMatrix, system::MolecularSystem};
+
+use super::{
+    core_hamiltonian, density, diagonalize, diis::Diis, orbital_gradient, orthogonalizer,
+    unrestricted_fock, ScfOptions, TwoElectron,
+};
+
+/// The converged result of a restricted open-shell Hartree-Fock calculation.
+#[derive(Clone, Debug)]
+pub struct RohfResult {
+    /// The total energy, including the nuclear repulsion energy
+    pub energy: f64,
+    pub nuclear_repulsion_energy: f64,
+    /// The eigenvalues of the effective Fock matrix in ascending order. Only the occupied ones of
+    /// the closed and of the open shells have a physical meaning as orbital energies.
+    pub orbital_energies: DVector<f64>,
+    /// The orbital coefficients shared by both spins, one orbital per column, in the order of
+    /// `orbital_energies`. The first `n_beta` orbitals are doubly occupied, the following
+    /// `n_alpha - n_beta` orbitals are singly occupied.
+    pub coefficients: DMatrix<f64>,
+    /// The [alpha, beta] density matrices
+    pub densities: [SymmetricMatrix; 2],
+    pub n_alpha: usize,
+    pub n_beta: usize,
+    /// The number of iterations needed for convergence
+    pub iterations: usize,
+}
+
+/// Runs a restricted open-shell Hartree-Fock calculation for the given [MolecularSystem], where
+/// alpha and beta electrons share the same spatial orbitals, starting from the core Hamiltonian
+/// guess.
+///
+/// The orbitals are obtained by diagonalizing a single effective Fock matrix with the canonicalization
+/// of Guest and Saunders, i.e., the average of the alpha and beta Fock matrices within the closed,
+/// the open and the virtual space and between the closed and the virtual space.
+///
+/// Fails if the iterations don't converge.
+///
+/// # References
+///
+/// [1] Guest, M. F.; Saunders, V. R. On methods for converging open-shell Hartree-Fock
+/// wave-functions. Mol. Phys. 1974, 28, 819–828
+pub fn rohf(system: &MolecularSystem, options: &ScfOptions) -> anyhow::Result<RohfResult> {
+    let (n_alpha, n_beta) = system.electron_counts()?;
+
+    let overlap = DMatrix::from(crate::overlap(system));
+    let hamiltonian = core_hamiltonian(system);
+    let orthogonalizer = orthogonalizer(&overlap, options)?;
+    ensure!(
+        n_alpha <= orthogonalizer.ncols(),
+        "{n_alpha} alpha electrons don't fit into {} orbitals",
+        orthogonalizer.ncols()
+    );
+
+    let two_electron = TwoElectron::new(system, options.two_electron);
+    let nuclear_repulsion_energy = system.nuclear_repulsion_energy();
+
+    let (_, mut coefficients) = diagonalize(&hamiltonian, &orthogonalizer);
+
+    let mut diis = Diis::new(options.diis_size);
+    let mut previous_energy = f64::NAN;
+    for iteration in 1..=options.max_iterations {
+        let densities = [n_alpha, n_beta].map(|n_occupied| density(&coefficients, n_occupied));
+        let (focks, energy) = unrestricted_fock(&hamiltonian, &two_electron, &densities);
+        let energy = energy + nuclear_repulsion_energy;
+
+        let fock = effective_fock(&focks, &coefficients, &overlap, n_alpha, n_beta);
+        let total_density = &densities[0] + &densities[1];
+        let error = orbital_gradient(&fock, &total_density, &overlap, &orthogonalizer);
+        let gradient = error.amax();
+
+        log::debug!("ROHF iteration {iteration}: energy {energy:.10}, gradient {gradient:.3e}");
+
+        if (energy - previous_energy).abs() < options.energy_threshold
+            && gradient < options.gradient_threshold
+        {
+            let (orbital_energies, coefficients) = diagonalize(&fock, &orthogonalizer);
+            return Ok(RohfResult {
+                energy,
+                nuclear_repulsion_energy,
+                orbital_energies,
+                coefficients,
+                densities: densities.each_ref().map(SymmetricMatrix::from),
+                n_alpha,
+                n_beta,
+                iterations: iteration,
+            });
+        }
+        previous_energy = energy;
+
+        let fock = diis.extrapolate(vec![fock], vec![error]).remove(0);
+        (_, coefficients) = diagonalize(&fock, &orthogonalizer);
+    }
+
+    bail!(
+        "ROHF did not converge within {} iterations",
+        options.max_iterations
+    )
+}
+
+/// Returns the effective Fock matrix in the atomic orbital basis, built from the alpha and beta
+/// Fock matrices in the basis of the current orbitals.
+///
+/// Between the closed and the open shells the beta Fock matrix is used, and between the open
+/// shells and the virtual orbitals the alpha one, such that the effective Fock matrix commutes
+/// with the total density exactly if the ROHF energy is stationary.
+fn effective_fock(
+    [alpha, beta]: &[DMatrix<f64>; 2],
+    coefficients: &DMatrix<f64>,
+    overlap: &DMatrix<f64>,
+    n_alpha: usize,
+    n_beta: usize,
+) -> DMatrix<f64> {
+    let alpha = coefficients.transpose() * alpha * coefficients;
+    let beta = coefficients.transpose() * beta * coefficients;
+
+    let mut fock = 0.5 * (&alpha + &beta);
+    for open in n_beta..n_alpha {
+        for closed in 0..n_beta {
+            fock[(closed, open)] = beta[(closed, open)];
+            fock[(open, closed)] = beta[(open, closed)];
+        }
+        for virtual_ in n_alpha..fock.ncols() {
+            fock[(open, virtual_)] = alpha[(open, virtual_)];
+            fock[(virtual_, open)] = alpha[(virtual_, open)];
+        }
+    }
+
+    let transformation = overlap * coefficients;
+    &transformation * fock * transformation.transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{DMatrix, Point3};
+
+    use crate::{
+        basis::BasisSet,
+        scf::{core_hamiltonian, unrestricted_fock, ScfOptions, TwoElectron, TwoElectronMethod},
+        system::{Atom, MolecularSystem},
+    };
+
+    #[test]
+    fn hydrogen_atom() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let atoms = [Atom::new(1, Point3::origin())];
+        let system = MolecularSystem::from_atoms(&atoms, &basis_set);
+        let result = super::rohf(&system, &ScfOptions::default()).unwrap();
+
+        approx::assert_abs_diff_eq!(result.energy, -0.466582, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn lithium_atom() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let atoms = [Atom::new(3, Point3::origin())];
+        let system = MolecularSystem::from_atoms(&atoms, &basis_set);
+        let result = super::rohf(&system, &ScfOptions::default()).unwrap();
+        assert_eq!((result.n_alpha, result.n_beta), (2, 1));
+
+        // HF/STO-3G energy of the doublet from the NIST CCCBDB, where the 1s and 2s orbitals are
+        // coupled through the block between the closed and the open shell
+        approx::assert_abs_diff_eq!(result.energy, -7.315526, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn stationary() {
+        let basis_set = BasisSet::load("data/basis/6-31G.json").unwrap();
+        let mut system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        system.charge = 1;
+        system.multiplicity = 2;
+        let result = super::rohf(&system, &ScfOptions::default()).unwrap();
+
+        // rotations between the closed and the open shells only move beta electrons, and those
+        // between the open shells and the virtual orbitals only alpha electrons, so the
+        // respective Fock matrix vanishes there at the energy minimum
+        let two_electron = TwoElectron::new(&system, TwoElectronMethod::Stored);
+        let densities = result.densities.each_ref().map(DMatrix::from);
+        let (focks, _) = unrestricted_fock(&core_hamiltonian(&system), &two_electron, &densities);
+        let [alpha, beta] =
+            focks.map(|fock| result.coefficients.transpose() * fock * &result.coefficients);
+
+        let (n_alpha, n_beta) = (result.n_alpha, result.n_beta);
+        let n_virtual = alpha.ncols() - n_alpha;
+        assert!(beta.view((0, n_beta), (n_beta, n_alpha - n_beta)).amax() < 1e-5);
+        assert!(
+            alpha
+                .view((n_beta, n_alpha), (n_alpha - n_beta, n_virtual))
+                .amax()
+                < 1e-5
+        );
+    }
+
+    #[test]
+    fn water_cation() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let mut system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let options = ScfOptions::default();
+
+        let restricted = crate::scf::rhf(&system, &options).unwrap();
+        let closed_shell = super::rohf(&system, &options).unwrap();
+        approx::assert_abs_diff_eq!(closed_shell.energy, restricted.energy, epsilon = 1e-8);
+
+        system.charge = 1;
+        system.multiplicity = 2;
+        let stored = super::rohf(&system, &options).unwrap();
+        assert_eq!((stored.n_alpha, stored.n_beta), (5, 4));
+
+        let options = ScfOptions {
+            two_electron: TwoElectronMethod::Direct,
+            ..Default::default()
+        };
+        let direct = super::rohf(&system, &options).unwrap();
+        approx::assert_abs_diff_eq!(direct.energy, stored.energy, epsilon = 1e-8);
+    }
+}
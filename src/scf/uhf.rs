@@ -0,0 +1,172 @@
+use anyhow::{bail, ensure};
+use nalgebra::{DMatrix, DVector};
+
+use crate::{storage::SymmetricMatrix, system::MolecularSystem};
+
+use super::{
+    core_hamiltonian, density, diagonalize, diis::Diis, orbital_gradient, orthogonalizer,
+    unrestricted_fock, ScfOptions, TwoElectron,
+};
+
+/// The converged result of an unrestricted Hartree-Fock calculation. All per-spin quantities are
+/// given as [alpha, beta].
+#[derive(Clone, Debug)]
+pub struct UhfResult {
+    /// The total energy, including the nuclear repulsion energy
+    pub energy: f64,
+    pub nuclear_repulsion_energy: f64,
+    /// The orbital energies in ascending order
+    pub orbital_energies: [DVector<f64>; 2],
+    /// The orbital coefficients, one orbital per column, in the order of `orbital_energies`
+    pub coefficients: [DMatrix<f64>; 2],
+    pub densities: [SymmetricMatrix; 2],
+    pub n_alpha: usize,
+    pub n_beta: usize,
+    /// The expectation value of the total spin operator S^2, which deviates from S(S + 1) in the
+    /// presence of spin contamination
+    pub spin_squared: f64,
+    /// The number of iterations needed for convergence
+    pub iterations: usize,
+}
+
+/// Runs an unrestricted Hartree-Fock calculation for the given [MolecularSystem], using separate
+/// spatial orbitals for alpha and beta electrons, starting from the core Hamiltonian guess.
+///
+/// Fails if the iterations don't converge.
+pub fn uhf(system: &MolecularSystem, options: &ScfOptions) -> anyhow::Result<UhfResult> {
+    let (n_alpha, n_beta) = system.electron_counts()?;
+    let occupations = [n_alpha, n_beta];
+
+    let overlap = DMatrix::from(crate::overlap(system));
+    let hamiltonian = core_hamiltonian(system);
+    let orthogonalizer = orthogonalizer(&overlap, options)?;
+    ensure!(
+        n_alpha <= orthogonalizer.ncols(),
+        "{n_alpha} alpha electrons don't fit into {} orbitals",
+        orthogonalizer.ncols()
+    );
+
+    let two_electron = TwoElectron::new(system, options.two_electron);
+    let nuclear_repulsion_energy = system.nuclear_repulsion_energy();
+
+    let (_, coefficients) = diagonalize(&hamiltonian, &orthogonalizer);
+    let mut densities = occupations.map(|n_occupied| density(&coefficients, n_occupied));
+
+    let mut diis = Diis::new(options.diis_size);
+    let mut previous_energy = f64::NAN;
+    for iteration in 1..=options.max_iterations {
+        let (focks, energy) = unrestricted_fock(&hamiltonian, &two_electron, &densities);
+        let energy = energy + nuclear_repulsion_energy;
+        let errors = [0, 1].map(|spin| {
+            orbital_gradient(&focks[spin], &densities[spin], &overlap, &orthogonalizer)
+        });
+        let gradient = errors[0].amax().max(errors[1].amax());
+
+        log::debug!("UHF iteration {iteration}: energy {energy:.10}, gradient {gradient:.3e}");
+
+        if (energy - previous_energy).abs() < options.energy_threshold
+            && gradient < options.gradient_threshold
+        {
+            let [(alpha_energies, alpha), (beta_energies, beta)] = focks
+                .each_ref()
+                .map(|fock| diagonalize(fock, &orthogonalizer));
+            let spin_squared = spin_squared(&densities, &overlap, n_alpha, n_beta);
+            return Ok(UhfResult {
+                energy,
+                nuclear_repulsion_energy,
+                orbital_energies: [alpha_energies, beta_energies],
+                coefficients: [alpha, beta],
+                densities: densities.each_ref().map(SymmetricMatrix::from),
+                n_alpha,
+                n_beta,
+                spin_squared,
+                iterations: iteration,
+            });
+        }
+        previous_energy = energy;
+
+        let focks = diis.extrapolate(focks.into(), errors.into());
+        for ((density, fock), n_occupied) in densities.iter_mut().zip(&focks).zip(occupations) {
+            let (_, coefficients) = diagonalize(fock, &orthogonalizer);
+            *density = super::density(&coefficients, n_occupied);
+        }
+    }
+
+    bail!(
+        "UHF did not converge within {} iterations",
+        options.max_iterations
+    )
+}
+
+/// Returns the expectation value of S^2 = S_z (S_z + 1) + n_beta - tr(D_alpha S D_beta S) for the
+/// given alpha and beta density matrices.
+fn spin_squared(
+    [alpha, beta]: &[DMatrix<f64>; 2],
+    overlap: &DMatrix<f64>,
+    n_alpha: usize,
+    n_beta: usize,
+) -> f64 {
+    let spin_z = 0.5 * (n_alpha as f64 - n_beta as f64);
+    let overlap_sum = (alpha * overlap * beta * overlap).trace();
+    spin_z * (spin_z + 1.0) + n_beta as f64 - overlap_sum
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point3;
+
+    use crate::{
+        basis::BasisSet,
+        scf::ScfOptions,
+        system::{Atom, MolecularSystem},
+    };
+
+    #[test]
+    fn hydrogen_atom() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let atoms = [Atom::new(1, Point3::origin())];
+        let system = MolecularSystem::from_atoms(&atoms, &basis_set);
+        let result = super::uhf(&system, &ScfOptions::default()).unwrap();
+
+        // the expectation value of the energy of the (normalized) STO-3G 1s function
+        approx::assert_abs_diff_eq!(result.energy, -0.466582, epsilon = 1e-6);
+        approx::assert_abs_diff_eq!(result.spin_squared, 0.75, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn ghost_atoms() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/hydrogen.json", &basis_set).unwrap();
+        let monomer = MolecularSystem::from_atoms(&system.atoms[..1], &basis_set);
+        let mut atoms = system.atoms.clone();
+        atoms[1] = atoms[1].ghost();
+        let ghost = MolecularSystem::from_atoms(&atoms, &basis_set);
+
+        let options = ScfOptions::default();
+        let monomer = super::uhf(&monomer, &options).unwrap();
+        let ghost = super::uhf(&ghost, &options).unwrap();
+
+        // the basis set superposition error lowers the energy of the monomer
+        assert_eq!(ghost.nuclear_repulsion_energy, 0.0);
+        assert!(ghost.energy < monomer.energy - 1e-4);
+    }
+
+    #[test]
+    fn water() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let mut system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let options = ScfOptions::default();
+
+        let restricted = crate::scf::rhf(&system, &options).unwrap();
+        let unrestricted = super::uhf(&system, &options).unwrap();
+        approx::assert_abs_diff_eq!(unrestricted.energy, restricted.energy, epsilon = 1e-8);
+        approx::assert_abs_diff_eq!(unrestricted.spin_squared, 0.0, epsilon = 1e-8);
+
+        system.charge = 1;
+        system.multiplicity = 2;
+        let cation = super::uhf(&system, &options).unwrap();
+        let restricted_open = crate::scf::rohf(&system, &options).unwrap();
+        assert!(cation.energy <= restricted_open.energy + 1e-8);
+        assert!(cation.spin_squared >= 0.75 - 1e-8);
+    }
+}
@@ -0,0 +1,138 @@
+use nalgebra::Vector3;
+use ndarray::Array4;
+
+use crate::system::MolecularSystem;
+
+use super::*;
+
+/// Stores the derivatives of the electron-electron repulsion energy integrals with respect to the
+/// centers of the four basis functions, exploiting the same symmetries as [EriTensor].
+///
+/// For each stored integral (ij|kl), only the derivatives with respect to the centers of i, j and
+/// k are kept, as the integrals are invariant under translation of all four centers. The entries
+/// are packed, so this type needs about half as many entries as an [EriTensor] of the same size,
+/// each of which holds nine derivatives.
+pub struct EriDerivativeTensor {
+    data: Vec<[Vector3<f64>; 3]>,
+    atoms: Vec<usize>,
+    n: usize,
+}
+
+impl EriDerivativeTensor {
+    /// Create and allocate an [EriDerivativeTensor] for the given [MolecularSystem] where all
+    /// entries are zero.
+    pub(crate) fn zeros(system: &MolecularSystem) -> Self {
+        let n = system.n_basis();
+        let n_pairs = n * (n + 1) / 2;
+
+        let mut atoms = vec![0; n];
+        for shell in 0..system.n_shells() {
+            let shell = system.shell_basis(shell);
+            atoms[shell.matrix_start_index..shell.matrix_start_index + shell.matrix_count]
+                .fill(shell.atom_index);
+        }
+
+        Self {
+            data: vec![[Vector3::zeros(); 3]; n_pairs * (n_pairs + 1) / 2],
+            atoms,
+            n,
+        }
+    }
+
+    /// Returns the number of basis functions per axis.
+    pub fn n_basis(&self) -> usize {
+        self.n
+    }
+
+    /// Returns the derivatives of the integral (ij|kl) with respect to the centers of the basis
+    /// functions i, j, k and l, in that order.
+    pub fn center_derivatives(&self, index: (usize, usize, usize, usize)) -> [Vector3<f64>; 4] {
+        let (canonical, permutation) = canonicalize_with_permutation(index);
+        let [a, b, c] = self.data[linearize(canonical)];
+        let stored = [a, b, c, -(a + b + c)];
+
+        let mut derivatives = [Vector3::zeros(); 4];
+        for (position, derivative) in permutation.into_iter().zip(stored) {
+            derivatives[position] = derivative;
+        }
+        derivatives
+    }
+
+    /// Returns the derivative of the integral (ij|kl) with respect to the position of the atom
+    /// with the given index.
+    pub fn derivative(
+        &self,
+        index: (usize, usize, usize, usize),
+        atom_index: usize,
+    ) -> Vector3<f64> {
+        let (i, j, k, l) = index;
+        [i, j, k, l]
+            .into_iter()
+            .zip(self.center_derivatives(index))
+            .filter(|&(function, _)| self.atoms[function] == atom_index)
+            .map(|(_, derivative)| derivative)
+            .sum()
+    }
+
+    /// Given the derivatives of a block of integrals with respect to its first three centers
+    /// (indexed by center and cartesian direction), copies the entries from the block starting at
+    /// (start_a, start_b, start_c, start_d) and extending for (count_a, count_b, count_c, count_d)
+    /// elements in their respective axes, to the correct positions of this [EriDerivativeTensor].
+    pub(crate) fn copy_from(
+        &mut self,
+        from: &[[Array4<f64>; 3]; 3],
+        (start_a, start_b, start_c, start_d): (usize, usize, usize, usize),
+        (count_a, count_b, count_c, count_d): (usize, usize, usize, usize),
+    ) {
+        for (i, a) in (start_a..start_a + count_a).enumerate() {
+            for (j, b) in (start_b..start_b + count_b).enumerate() {
+                for (k, c) in (start_c..start_c + count_c).enumerate() {
+                    for (l, d) in (start_d..start_d + count_d).enumerate() {
+                        let [da, db, dc] = from.each_ref().map(|center| {
+                            Vector3::new(
+                                center[0][(i, j, k, l)],
+                                center[1][(i, j, k, l)],
+                                center[2][(i, j, k, l)],
+                            )
+                        });
+                        let derivatives = [da, db, dc, -(da + db + dc)];
+
+                        let (canonical, permutation) = canonicalize_with_permutation((a, b, c, d));
+                        self.data[linearize(canonical)] =
+                            [0, 1, 2].map(|position| derivatives[permutation[position]]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Like [canonicalize_4d_index], but additionally returns for every position of the canonical index
+/// the position in the given index it was taken from.
+fn canonicalize_with_permutation(
+    (i, j, k, l): (usize, usize, usize, usize),
+) -> ((usize, usize, usize, usize), [usize; 4]) {
+    let (mut index, mut permutation) = ([i, j, k, l], [0, 1, 2, 3]);
+    if index[0] > index[1] {
+        index.swap(0, 1);
+        permutation.swap(0, 1);
+    }
+    if index[2] > index[3] {
+        index.swap(2, 3);
+        permutation.swap(2, 3);
+    }
+    if pair_index((index[0], index[1])) > pair_index((index[2], index[3])) {
+        index.rotate_left(2);
+        permutation.rotate_left(2);
+    }
+
+    let [i, j, k, l] = index;
+    ((i, j, k, l), permutation)
+}
+
+/// Returns the position of a canonical index in the packed storage.
+const fn linearize((i, j, k, l): (usize, usize, usize, usize)) -> usize {
+    let ij = pair_index((i, j));
+    let kl = pair_index((k, l));
+    kl * (kl + 1) / 2 + ij
+}
@@ -10,7 +10,7 @@ use super::*;
 /// These symmetries are exploited by only storing integrals (ij|kl) such that:
 ///     (i)     i <= j
 ///     (ii)    k <= l
-///     (iii)   i * (i + 1) / 2 + j <= k * (k + 1) / 2 + l
+///     (iii)   j * (j + 1) / 2 + i <= l * (l + 1) / 2 + k
 ///
 /// These constraints make sure that no redundant integrals (i.e, integrals that are equivalent by
 /// the inherent symmetry of the formula) are stored twice.
@@ -33,7 +33,9 @@ impl EriTensor {
 
     /// Given an [Array4], copies the entries from the block starting at
     /// (start_a, start_b, start_c, start_d) and extending for (count_a, count_b, count_c, count_d)
-    /// elements in their respective axes, to the correct positions of this [EriTensor]
+    /// elements in their respective axes, to the correct positions of this [EriTensor].
+    /// As the whole block is copied, entries that are equivalent by symmetry are written more than
+    /// once, so they have to agree.
     pub(crate) fn copy_from(
         &mut self,
         from: &Array4<f64>,
@@ -41,25 +43,50 @@ impl EriTensor {
         (count_a, count_b, count_c, count_d): (usize, usize, usize, usize),
     ) {
         for (i, a) in (start_a..start_a + count_a).enumerate() {
-            for (j, b) in (start_b..start_b + count_b)
-                .enumerate()
-                .skip_while(|&(_, b)| a > b)
-            {
-                let ab = a * (a + 1) / 2 + b;
+            for (j, b) in (start_b..start_b + count_b).enumerate() {
                 for (k, c) in (start_c..start_c + count_c).enumerate() {
-                    for (l, d) in (start_d..start_d + count_d)
-                        .enumerate()
-                        .skip_while(|&(_, d)| c > d || ab > c * (c + 1) / 2 + d)
-                    {
-                        *self.index_unchecked_mut((a, b, c, d)) = from[(i, j, k, l)];
+                    for (l, d) in (start_d..start_d + count_d).enumerate() {
+                        self[(a, b, c, d)] = from[(i, j, k, l)];
                     }
                 }
             }
         }
     }
 
-    fn index_unchecked_mut(&mut self, index: (usize, usize, usize, usize)) -> &mut f64 {
-        &mut self.data[linearize_symmetric_4d(self.n, index)]
+    /// Returns the Coulomb matrix J_ij = sum_kl (ij|kl) D_kl for the given density matrix D.
+    pub fn coulomb(&self, density: &SymmetricMatrix) -> SymmetricMatrix {
+        let mut accumulator = JkAccumulator::new(density);
+        self.for_each_unique(|index, value| accumulator.add_coulomb(index, value));
+        accumulator.coulomb()
+    }
+
+    /// Returns the exchange matrix K_ij = sum_kl (ik|jl) D_kl for the given density matrix D.
+    pub fn exchange(&self, density: &SymmetricMatrix) -> SymmetricMatrix {
+        let mut accumulator = JkAccumulator::new(density);
+        self.for_each_unique(|index, value| accumulator.add_exchange(index, value));
+        accumulator.exchange()
+    }
+
+    /// Calls the given function for every stored (i.e., symmetry-unique) non-zero integral.
+    fn for_each_unique(&self, mut f: impl FnMut((usize, usize, usize, usize), f64)) {
+        for l in 0..self.n {
+            for k in 0..=l {
+                let kl = pair_index((k, l));
+                for j in 0..=l {
+                    for i in 0..=j {
+                        if pair_index((i, j)) > kl {
+                            break;
+                        }
+
+                        let index = (i, j, k, l);
+                        let value = self.data[linearize_symmetric_4d(self.n, index)];
+                        if value != 0.0 {
+                            f(index, value);
+                        }
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -78,3 +105,38 @@ impl std::ops::IndexMut<(usize, usize, usize, usize)> for EriTensor {
         &mut self.data[linearize_symmetric_4d(self.n, index)]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::DMatrix;
+
+    use crate::{basis::BasisSet, storage::SymmetricMatrix, system::MolecularSystem};
+
+    #[test]
+    fn coulomb_exchange() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let eri = crate::eri(&system);
+
+        let n = system.n_basis();
+        let density = DMatrix::from_fn(n, n, |i, j| 0.3 * f64::sin((i * j + 1) as f64));
+        let density = SymmetricMatrix::from(&(&density + density.transpose()));
+
+        let coulomb = eri.coulomb(&density);
+        let exchange = eri.exchange(&density);
+        for i in 0..n {
+            for j in 0..n {
+                let mut expected_coulomb = 0.0;
+                let mut expected_exchange = 0.0;
+                for k in 0..n {
+                    for l in 0..n {
+                        expected_coulomb += eri[(i, j, k, l)] * density[(k, l)];
+                        expected_exchange += eri[(i, k, j, l)] * density[(k, l)];
+                    }
+                }
+                approx::assert_abs_diff_eq!(coulomb[(i, j)], expected_coulomb, epsilon = 1e-10);
+                approx::assert_abs_diff_eq!(exchange[(i, j)], expected_exchange, epsilon = 1e-10);
+            }
+        }
+    }
+}
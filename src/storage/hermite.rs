@@ -87,7 +87,7 @@ pub struct HermiteCache {
 impl HermiteCache {
     /// Precomputes the [HermiteCache] for a given [MolecularSystem]
     pub fn new(system: &MolecularSystem) -> Self {
-        let n_basis = system.n_basis();
+        let n_basis = system.basis.len();
         let n_shells = system.shells.len();
 
         let mut data: Vec<_> = (0..n_basis * n_basis).map(|_| None).collect();
@@ -108,7 +108,7 @@ impl HermiteCache {
                     ..
                 } = system.shell_basis(b);
 
-                let diff_ab = pos_b - pos_a;
+                let diff_ab = pos_a - pos_b;
 
                 for i in start_a..start_a + count_a {
                     for j in start_b..start_b + count_b {
@@ -128,6 +128,23 @@ impl HermiteCache {
         }
     }
 
+    /// Precomputes the [ExpansionCoefficients] between each basis function of the given
+    /// [MolecularSystem] and the given function with exponent 0 at the same center, which turns
+    /// the basis functions into charge distributions of their own. They are indexed as the pair
+    /// (i, 0).
+    pub fn with_unit(system: &MolecularSystem, unit: &ContractedGaussian) -> Self {
+        Self {
+            data: system
+                .basis
+                .iter()
+                .map(|function| {
+                    ExpansionCoefficients::from_basis_pair(function, unit, Vector3::zeros())
+                })
+                .collect(),
+            n: 1,
+        }
+    }
+
     /// Returns the [ExpansionCoefficients] between basis function i and j
     pub fn basis_pair(&self, i: usize, j: usize) -> &ExpansionCoefficients {
         let linear = self.n * i + j;
@@ -135,6 +152,83 @@ impl HermiteCache {
     }
 }
 
+/// A term of the derivative of a [ContractedGaussian] with respect to its center (see
+/// [ContractedGaussian::center_derivative]), along with its [ExpansionCoefficients] with another
+/// basis function.
+pub struct DerivativeTerm {
+    pub(crate) basis: ContractedGaussian,
+    pub(crate) expansion: ExpansionCoefficients,
+}
+
+/// Stores the [DerivativeTerm]s of the first basis function of all pairs of basis functions, for
+/// each cartesian direction. Derivatives of the second basis function are obtained from the
+/// swapped pair.
+pub struct HermiteDerivativeCache {
+    data: Vec<[SmallVec<[DerivativeTerm; 2]>; 3]>,
+    n: usize,
+}
+
+impl HermiteDerivativeCache {
+    /// Precomputes the [HermiteDerivativeCache] for a given [MolecularSystem]
+    pub fn new(system: &MolecularSystem) -> Self {
+        let n_basis = system.basis.len();
+        let n_shells = system.shells.len();
+
+        let mut data: Vec<_> = (0..n_basis * n_basis).map(|_| None).collect();
+
+        for a in 0..n_shells {
+            for b in 0..n_shells {
+                let ShellBasis {
+                    start_index: start_a,
+                    count: count_a,
+                    center: pos_a,
+                    ..
+                } = system.shell_basis(a);
+
+                let ShellBasis {
+                    start_index: start_b,
+                    count: count_b,
+                    center: pos_b,
+                    ..
+                } = system.shell_basis(b);
+
+                let diff_ab = pos_a - pos_b;
+
+                for i in start_a..start_a + count_a {
+                    for j in start_b..start_b + count_b {
+                        data[n_basis * i + j] = Some([0, 1, 2].map(|axis| {
+                            system.basis[i]
+                                .center_derivative(axis)
+                                .into_iter()
+                                .map(|basis| DerivativeTerm {
+                                    expansion: ExpansionCoefficients::from_basis_pair(
+                                        &basis,
+                                        system.basis[j],
+                                        diff_ab,
+                                    ),
+                                    basis,
+                                })
+                                .collect()
+                        }));
+                    }
+                }
+            }
+        }
+
+        Self {
+            data: data.into_iter().map(Option::unwrap).collect_vec(),
+            n: n_basis,
+        }
+    }
+
+    /// Returns the [DerivativeTerm]s of basis function i in the pair with basis function j, for
+    /// each cartesian direction
+    pub fn basis_pair(&self, i: usize, j: usize) -> &[SmallVec<[DerivativeTerm; 2]>; 3] {
+        let linear = self.n * i + j;
+        &self.data[linear]
+    }
+}
+
 fn hermite_expansion_simd(
     [i, j, t]: [i32; 3],
     dist: f64,
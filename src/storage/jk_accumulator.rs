@@ -0,0 +1,77 @@
+use nalgebra::DMatrix;
+
+use super::*;
+
+/// Accumulates the Coulomb matrix J_ij = sum_kl (ij|kl) D_kl and the exchange matrix
+/// K_ij = sum_kl (ik|jl) D_kl from the symmetry-unique integrals (ij|kl) with i <= j, k <= l and
+/// (i, j) <= (k, l) (see [EriTensor]).
+///
+/// Each unique integral is scattered to all of its equivalent permutations at once. Weighting the
+/// integral with the number of equivalent permutations and symmetrizing at the end gives the same
+/// result, but with fewer cases to distinguish.
+pub(crate) struct JkAccumulator {
+    density: DMatrix<f64>,
+    coulomb: DMatrix<f64>,
+    exchange: DMatrix<f64>,
+}
+
+impl JkAccumulator {
+    pub(crate) fn new(density: &SymmetricMatrix) -> Self {
+        let n = density.n();
+        Self {
+            density: DMatrix::from(density),
+            coulomb: DMatrix::zeros(n, n),
+            exchange: DMatrix::zeros(n, n),
+        }
+    }
+
+    /// Adds the contributions of the unique integral (ij|kl) to the Coulomb matrix.
+    pub(crate) fn add_coulomb(
+        &mut self,
+        index @ (i, j, k, l): (usize, usize, usize, usize),
+        value: f64,
+    ) {
+        let value = value * degeneracy(index);
+        self.coulomb[(i, j)] += self.density[(k, l)] * value;
+        self.coulomb[(k, l)] += self.density[(i, j)] * value;
+    }
+
+    /// Adds the contributions of the unique integral (ij|kl) to the exchange matrix.
+    pub(crate) fn add_exchange(
+        &mut self,
+        index @ (i, j, k, l): (usize, usize, usize, usize),
+        value: f64,
+    ) {
+        let value = value * degeneracy(index);
+        self.exchange[(i, k)] += self.density[(j, l)] * value;
+        self.exchange[(j, l)] += self.density[(i, k)] * value;
+        self.exchange[(i, l)] += self.density[(j, k)] * value;
+        self.exchange[(j, k)] += self.density[(i, l)] * value;
+    }
+
+    /// Adds the contributions of the unique integral (ij|kl) to both matrices.
+    pub(crate) fn add(&mut self, index: (usize, usize, usize, usize), value: f64) {
+        self.add_coulomb(index, value);
+        self.add_exchange(index, value);
+    }
+
+    /// Returns the Coulomb matrix.
+    pub(crate) fn coulomb(&self) -> SymmetricMatrix {
+        // due to the degeneracy weighting, the accumulated matrix and its transpose add up to 4 J
+        SymmetricMatrix::from(&((&self.coulomb + self.coulomb.transpose()) * 0.25))
+    }
+
+    /// Returns the exchange matrix.
+    pub(crate) fn exchange(&self) -> SymmetricMatrix {
+        // due to the degeneracy weighting, the accumulated matrix and its transpose add up to 8 K
+        SymmetricMatrix::from(&((&self.exchange + self.exchange.transpose()) * 0.125))
+    }
+}
+
+/// Returns the number of distinct permutations of the unique integral (ij|kl)
+fn degeneracy((i, j, k, l): (usize, usize, usize, usize)) -> f64 {
+    let ij = if i == j { 1.0 } else { 2.0 };
+    let kl = if k == l { 1.0 } else { 2.0 };
+    let ij_kl = if (i, j) == (k, l) { 1.0 } else { 2.0 };
+    ij * kl * ij_kl
+}
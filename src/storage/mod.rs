@@ -1,10 +1,14 @@
 //! This module contains types that are used to either store or cache stuff.
 
+mod eri_derivative_tensor;
 mod eri_tensor;
 pub(crate) mod hermite;
+mod jk_accumulator;
 mod symmetric_matrix;
 
+pub use eri_derivative_tensor::EriDerivativeTensor;
 pub use eri_tensor::EriTensor;
+pub(crate) use jk_accumulator::JkAccumulator;
 pub use symmetric_matrix::SymmetricMatrix;
 
 pub(super) const fn linearize_upper_triangular(n: usize, (i, j): (usize, usize)) -> usize {
@@ -31,18 +35,23 @@ pub(super) const fn canonicalize_2d_index((i, j): (usize, usize)) -> (usize, usi
     }
 }
 
+/// Returns a unique index for the index pair (i, j) with i <= j, which is used to order index pairs
+pub(super) const fn pair_index((i, j): (usize, usize)) -> usize {
+    j * (j + 1) / 2 + i
+}
+
 /// if necessary, permute (i, j, k, l) such that
 ///  1. i <= j
 ///  2. k <= l
-///  3. i(i+1)/2+j <= k(k+1)/2+l
+///  3. j(j+1)/2+i <= l(l+1)/2+k
 pub(super) const fn canonicalize_4d_index(
     (i, j, k, l): (usize, usize, usize, usize),
 ) -> (usize, usize, usize, usize) {
     let (i, j) = canonicalize_2d_index((i, j));
     let (k, l) = canonicalize_2d_index((k, l));
 
-    let ij = i * (i + 1) / 2 + j;
-    let kl = k * (k + 1) / 2 + l;
+    let ij = pair_index((i, j));
+    let kl = pair_index((k, l));
 
     if ij <= kl {
         (i, j, k, l)
@@ -3,6 +3,7 @@ use nalgebra::DMatrix;
 use super::*;
 
 /// Represents a symmetric matrix, and efficiently stores it by only storing it's upper triangle
+#[derive(Clone, Debug)]
 pub struct SymmetricMatrix {
     data: Vec<f64>,
     n: usize,
@@ -36,6 +37,11 @@ impl SymmetricMatrix {
         }
     }
 
+    /// Returns the number of rows (and columns) of this matrix.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
     fn index_unchecked_mut(&mut self, index: (usize, usize)) -> &mut f64 {
         &mut self.data[linearize_upper_triangular(self.n, index)]
     }
@@ -68,3 +74,19 @@ impl From<&SymmetricMatrix> for DMatrix<f64> {
         DMatrix::from_fn(value.n, value.n, |i, j| value[(i, j)])
     }
 }
+
+impl From<&DMatrix<f64>> for SymmetricMatrix {
+    /// Takes the upper triangle of the given square matrix.
+    fn from(value: &DMatrix<f64>) -> Self {
+        assert!(value.is_square(), "a symmetric matrix has to be square");
+
+        let n = value.nrows();
+        let mut output = Self::zeros(n);
+        for i in 0..n {
+            for j in i..n {
+                *output.index_unchecked_mut((i, j)) = value[(i, j)];
+            }
+        }
+        output
+    }
+}
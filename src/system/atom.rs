@@ -1,9 +1,72 @@
 use nalgebra::Point3;
 
 /// Represents an atom in a [crate::system::MolecularSystem]
+///
+/// Besides regular atoms, this can represent ghost atoms, which carry the basis functions of
+/// their element but neither nuclear charge nor electrons (e.g. for counterpoise corrections),
+/// and bare point charges without basis functions.
+///
+/// Atoms are created with [Atom::new] or [Atom::point_charge], and custom nuclear charges and
+/// ghost atoms are set with [Atom::with_charge] and [Atom::ghost].
 #[derive(Copy, Clone, Debug)]
 pub struct Atom {
-    /// also the charge of the nucleus
+    /// Selects the basis functions and is also the charge of the nucleus, unless a custom charge
+    /// is given
     pub ordinal: usize,
     pub position: Point3<f64>,
+    charge: Option<f64>,
+    has_basis: bool,
+}
+
+impl Atom {
+    /// Creates an [Atom] with the nuclear charge given by its ordinal.
+    pub const fn new(ordinal: usize, position: Point3<f64>) -> Self {
+        Self {
+            ordinal,
+            position,
+            charge: None,
+            has_basis: true,
+        }
+    }
+
+    /// Creates a bare point charge without basis functions.
+    pub const fn point_charge(charge: f64, position: Point3<f64>) -> Self {
+        Self {
+            ordinal: 0,
+            position,
+            charge: Some(charge),
+            has_basis: false,
+        }
+    }
+
+    /// Returns this atom with a custom charge of the nucleus, which replaces the ordinal in the
+    /// nuclear attraction and repulsion. The basis functions are still chosen by the ordinal.
+    pub const fn with_charge(self, charge: f64) -> Self {
+        Self {
+            charge: Some(charge),
+            ..self
+        }
+    }
+
+    /// Returns this atom as a ghost atom, which carries the basis functions of its element, but no
+    /// nuclear charge.
+    pub const fn ghost(self) -> Self {
+        self.with_charge(0.0)
+    }
+
+    /// Returns the custom charge of the nucleus, if any, see [Atom::with_charge].
+    pub fn charge(&self) -> Option<f64> {
+        self.charge
+    }
+
+    /// Returns whether basis functions are centered on this atom, which is false for point
+    /// charges.
+    pub fn has_basis(&self) -> bool {
+        self.has_basis
+    }
+
+    /// Returns the charge of the nucleus of this atom.
+    pub fn nuclear_charge(&self) -> f64 {
+        self.charge.unwrap_or(self.ordinal as f64)
+    }
 }
@@ -1,22 +1,208 @@
+use anyhow::{ensure, Context};
+use itertools::Itertools;
 use nalgebra::Point3;
 use serde::{Deserialize, Serialize};
 
-use super::Atom;
+use crate::basis::BasisSet;
+
+use super::{
+    xyz::parse_symbol, Atom, MolecularSystem, NuclearModel, PointCharge, ANGSTROM_TO_BOHR,
+};
 
 /// A helper type for serialization of [Atom]s
 #[derive(Debug, Serialize, Deserialize)]
 pub(super) struct ConfigAtom {
-    element: String,
+    /// Either the element symbol or the ordinal. Atoms without element are bare point charges.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    element: Option<String>,
     position: [f64; 3],
+    /// A custom nuclear charge
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    charge: Option<f64>,
+    /// Ghost atoms carry basis functions, but no nuclear charge
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    ghost: bool,
+}
+
+/// The unit of length of the positions in a [ConfigMolecule]
+#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum Units {
+    #[default]
+    Bohr,
+    Angstrom,
+}
+
+/// A helper type for serialization of [PointCharge]s
+#[derive(Debug, Deserialize)]
+pub(super) struct ConfigPointCharge {
+    charge: f64,
+    position: [f64; 3],
+}
+
+/// A helper type for serialization of molecules. Either a plain list of atoms with positions in
+/// Bohr, or an object with the atoms and further properties of the system.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub(super) enum ConfigMolecule {
+    Atoms(Vec<ConfigAtom>),
+    Molecule(ConfigSystem),
+}
+
+/// The properties of a system besides its atoms, which all have defaults
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct ConfigSystem {
+    atoms: Vec<ConfigAtom>,
+    #[serde(default)]
+    units: Units,
+    #[serde(default)]
+    charge: i32,
+    /// Defaults to the lowest possible spin state
+    multiplicity: Option<usize>,
+    #[serde(default)]
+    external_charges: Vec<ConfigPointCharge>,
+    #[serde(default)]
+    nuclear_model: NuclearModel,
+}
+
+impl ConfigMolecule {
+    /// Creates the [MolecularSystem] described by this molecule in the given [BasisSet].
+    pub(super) fn into_system(self, basis_set: &BasisSet) -> anyhow::Result<MolecularSystem<'_>> {
+        let ConfigSystem {
+            atoms,
+            units,
+            charge,
+            multiplicity,
+            external_charges,
+            nuclear_model,
+        } = match self {
+            Self::Atoms(atoms) => ConfigSystem {
+                atoms,
+                ..Default::default()
+            },
+            Self::Molecule(system) => system,
+        };
+
+        let scale = match units {
+            Units::Bohr => 1.0,
+            Units::Angstrom => ANGSTROM_TO_BOHR,
+        };
+        let atoms: Vec<Atom> = atoms
+            .into_iter()
+            .map(|atom| {
+                let mut atom = Atom::try_from(atom)?;
+                atom.position *= scale;
+                anyhow::Ok(atom)
+            })
+            .try_collect()?;
+
+        let mut system = MolecularSystem::from_atoms(&atoms, basis_set);
+        system.charge = charge;
+        if let Some(multiplicity) = multiplicity {
+            system.multiplicity = multiplicity;
+        } else if charge % 2 != 0 {
+            system.multiplicity = 3 - system.multiplicity;
+        }
+        system.nuclear_model = nuclear_model;
+        system.external_charges = external_charges
+            .into_iter()
+            .map(|point_charge| PointCharge {
+                charge: point_charge.charge,
+                position: Point3::from(point_charge.position) * scale,
+            })
+            .collect();
+
+        Ok(system)
+    }
 }
 
 impl TryFrom<ConfigAtom> for Atom {
     type Error = anyhow::Error;
 
     fn try_from(value: ConfigAtom) -> Result<Self, Self::Error> {
-        Ok(Self {
-            ordinal: value.element.parse()?,
-            position: Point3::from(value.position),
+        let position = Point3::from(value.position);
+        let Some(element) = value.element else {
+            ensure!(!value.ghost, "ghost atoms need an element");
+            let charge = value.charge.context("point charges need a charge")?;
+            return Ok(Self::point_charge(charge, position));
+        };
+
+        let ordinal = match element.parse::<usize>() {
+            Ok(ordinal) => ordinal,
+            Err(_) => parse_symbol(&element)? as usize,
+        };
+        ensure!(
+            !(value.ghost && value.charge.is_some()),
+            "ghost atoms can't have a charge"
+        );
+
+        Ok(if value.ghost {
+            Self::new(ordinal, position).ghost()
+        } else if let Some(charge) = value.charge {
+            Self::new(ordinal, position).with_charge(charge)
+        } else {
+            Self::new(ordinal, position)
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::basis::BasisSet;
+
+    use crate::system::NuclearModel;
+
+    use super::ConfigMolecule;
+
+    #[test]
+    fn molecule_object() {
+        let json = r#"{
+            "units": "angstrom",
+            "charge": 1,
+            "multiplicity": 2,
+            "atoms": [
+                { "element": "O", "position": [0.0, 0.0, 0.0] },
+                { "element": "1", "position": [0.0, 0.0, 1.0], "charge": 0.5 },
+                { "element": "H", "position": [0.0, 1.0, 0.0], "ghost": true },
+                { "position": [1.0, 0.0, 0.0], "charge": -0.8 }
+            ],
+            "nuclear_model": "gaussian",
+            "external_charges": [
+                { "charge": 0.4, "position": [0.0, 0.0, 2.0] }
+            ]
+        }"#;
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let molecule: ConfigMolecule = serde_json::from_str(json).unwrap();
+        let system = molecule.into_system(&basis_set).unwrap();
+        let atoms = &system.atoms;
+
+        assert_eq!((system.charge, system.multiplicity), (1, 2));
+        assert_eq!(system.nuclear_model, NuclearModel::Gaussian);
+        assert_eq!(atoms[0].ordinal, 8);
+        assert_eq!(atoms[1].nuclear_charge(), 0.5);
+        approx::assert_abs_diff_eq!(atoms[1].position.z, 1.8897261246, epsilon = 1e-9);
+        assert!(atoms[2].has_basis());
+        assert_eq!(atoms[2].nuclear_charge(), 0.0);
+        assert!(!atoms[3].has_basis());
+        assert_eq!(atoms[3].nuclear_charge(), -0.8);
+        assert_eq!(system.external_charges[0].charge, 0.4);
+        approx::assert_abs_diff_eq!(
+            system.external_charges[0].position.z,
+            2.0 * 1.8897261246,
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn atom_list() {
+        let json = r#"[{ "element": "Cl", "position": [0.0, 0.0, 1.0] }]"#;
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let molecule: ConfigMolecule = serde_json::from_str(json).unwrap();
+        let system = molecule.into_system(&basis_set).unwrap();
+
+        assert_eq!((system.charge, system.multiplicity), (0, 2));
+        assert_eq!(system.atoms[0].ordinal, 17);
+        assert_eq!(system.atoms[0].position.z, 1.0);
+        assert!(system.external_charges.is_empty());
+    }
+}
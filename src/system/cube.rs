@@ -0,0 +1,189 @@
+use std::io::Write;
+
+use anyhow::ensure;
+use nalgebra::{Point3, Vector3};
+
+use super::MolecularSystem;
+
+/// A regular 3D grid of points, as used by Gaussian cube files. All lengths are in Bohr.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CubeGrid {
+    pub origin: Point3<f64>,
+    /// The step between neighbouring points along each of the three axes
+    pub axes: [Vector3<f64>; 3],
+    /// The number of points along each of the three axes
+    pub counts: [usize; 3],
+}
+
+impl CubeGrid {
+    /// Creates an axis-aligned grid with the given spacing, which contains all atoms of the given
+    /// [MolecularSystem] with the given margin on every side.
+    pub fn around(system: &MolecularSystem, spacing: f64, margin: f64) -> Self {
+        let mut min = Point3::from([f64::INFINITY; 3]);
+        let mut max = Point3::from([f64::NEG_INFINITY; 3]);
+        for atom in &system.atoms {
+            min = min.inf(&atom.position);
+            max = max.sup(&atom.position);
+        }
+        let origin = min - Vector3::repeat(margin);
+        let extent = max - min + Vector3::repeat(2.0 * margin);
+
+        CubeGrid {
+            origin,
+            axes: [Vector3::x(), Vector3::y(), Vector3::z()].map(|axis| axis * spacing),
+            counts: extent
+                .map(|length| (length / spacing).ceil() as usize + 1)
+                .into(),
+        }
+    }
+
+    pub fn n_points(&self) -> usize {
+        self.counts.iter().product()
+    }
+
+    /// Returns the points of this grid in the order of cube files, i.e., with the index along the
+    /// last axis changing fastest.
+    pub fn points(&self) -> Vec<Point3<f64>> {
+        let [n_x, n_y, n_z] = self.counts;
+        let mut points = Vec::with_capacity(self.n_points());
+        for i in 0..n_x {
+            for j in 0..n_y {
+                for k in 0..n_z {
+                    points.push(
+                        self.origin
+                            + self.axes[0] * i as f64
+                            + self.axes[1] * j as f64
+                            + self.axes[2] * k as f64,
+                    );
+                }
+            }
+        }
+        points
+    }
+
+    /// Returns the volume of a single cell of this grid, e.g., to integrate values on it.
+    pub fn cell_volume(&self) -> f64 {
+        self.axes[0].cross(&self.axes[1]).dot(&self.axes[2]).abs()
+    }
+}
+
+/// Writes the given values on the given grid (in the order of [CubeGrid::points]) in the Gaussian
+/// cube format, along with the atoms of the given [MolecularSystem].
+pub fn write_cube(
+    mut writer: impl Write,
+    system: &MolecularSystem,
+    grid: &CubeGrid,
+    values: &[f64],
+    comment: &str,
+) -> anyhow::Result<()> {
+    ensure!(grid.n_points() > 0, "the grid contains no points");
+    ensure!(
+        values.len() == grid.n_points(),
+        "expected {} values for the grid, got {}",
+        grid.n_points(),
+        values.len()
+    );
+
+    writeln!(writer, "{}", comment.replace('\n', " "))?;
+    writeln!(writer, "written by molint")?;
+    let [x, y, z] = grid.origin.coords.into();
+    writeln!(
+        writer,
+        "{:5} {x:12.6} {y:12.6} {z:12.6}",
+        system.atoms.len()
+    )?;
+    for (count, axis) in grid.counts.iter().zip(&grid.axes) {
+        let [x, y, z] = (*axis).into();
+        writeln!(writer, "{count:5} {x:12.6} {y:12.6} {z:12.6}")?;
+    }
+    for (i, atom) in system.atoms.iter().enumerate() {
+        let [x, y, z] = atom.position.coords.into();
+        writeln!(
+            writer,
+            "{:5} {:12.6} {x:12.6} {y:12.6} {z:12.6}",
+            atom.ordinal,
+            system.nuclear_charge(i)
+        )?;
+    }
+
+    // the values of each line along the last axis start on a new line
+    for line in values.chunks(grid.counts[2]) {
+        for chunk in line.chunks(6) {
+            for value in chunk {
+                write!(writer, " {:>12}", scientific(*value))?;
+            }
+            writeln!(writer)?;
+        }
+    }
+    Ok(())
+}
+
+/// Formats a value like the `%12.5E` of C, e.g. as `1.23450E+00`, since Rust leaves out the sign
+/// and the leading zero of the exponent.
+fn scientific(value: f64) -> String {
+    let formatted = format!("{value:.5E}");
+    match formatted.split_once('E') {
+        Some((mantissa, exponent)) => {
+            let exponent: i32 = exponent.parse().unwrap();
+            format!("{mantissa}E{exponent:+03}")
+        }
+        // not a finite number
+        None => formatted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{basis::BasisSet, system::MolecularSystem};
+
+    use super::CubeGrid;
+
+    #[test]
+    fn write_cube() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let grid = CubeGrid::around(&system, 0.5, 3.0);
+        let points = grid.points();
+        assert_eq!(points.len(), grid.n_points());
+        for point in [points[0], points[points.len() - 1]] {
+            for atom in &system.atoms {
+                assert!((atom.position - point).abs().max() >= 3.0);
+            }
+        }
+
+        let values: Vec<_> = points.iter().map(|point| point.coords.norm()).collect();
+        let mut written = Vec::new();
+        super::write_cube(&mut written, &system, &grid, &values, "distance").unwrap();
+        let written = String::from_utf8(written).unwrap();
+        let lines: Vec<_> = written.lines().collect();
+        assert_eq!(lines[0], "distance");
+        assert_eq!(lines[2].split_whitespace().next(), Some("3"));
+        assert_eq!(
+            lines[6].split_whitespace().next(),
+            Some(system.atoms[0].ordinal.to_string().as_str())
+        );
+
+        // each line along the last axis starts on a new line with at most six values
+        let [n_x, n_y, n_z] = grid.counts;
+        let data = &lines[2 + 4 + system.atoms.len()..];
+        assert_eq!(data.len(), n_x * n_y * n_z.div_ceil(6));
+        let parsed: Vec<f64> = data
+            .iter()
+            .flat_map(|line| line.split_whitespace())
+            .map(|value| value.parse().unwrap())
+            .collect();
+        for (parsed, value) in parsed.iter().zip(&values) {
+            approx::assert_relative_eq!(parsed, value, max_relative = 1e-5);
+        }
+        assert_eq!(parsed.len(), values.len());
+        assert!(data[0].starts_with(&format!(" {:>12}", super::scientific(values[0]))));
+
+        assert_eq!(super::scientific(1.2345), "1.23450E+00");
+        assert_eq!(super::scientific(-0.00012345), "-1.23450E-04");
+        assert_eq!(super::scientific(6.02214076e23), "6.02214E+23");
+        assert_eq!(super::scientific(0.0), "0.00000E+00");
+        assert_eq!(super::scientific(1e-100), "1.00000E-100");
+
+        assert!(super::write_cube(Vec::new(), &system, &grid, &values[1..], "").is_err());
+    }
+}
@@ -3,9 +3,19 @@
 
 mod atom;
 mod config_atom;
+mod cube;
 mod molecule;
+mod nuclear_model;
+mod point_charge;
 mod shell;
+mod xyz;
+mod zmatrix;
 
 pub use atom::Atom;
+pub use cube::{write_cube, CubeGrid};
 pub use molecule::MolecularSystem;
+pub use nuclear_model::NuclearModel;
+pub use point_charge::PointCharge;
 pub(crate) use shell::{ShellBasis, ShellType};
+pub use xyz::{read_xyz, write_xyz, XyzFrame, ANGSTROM_TO_BOHR};
+pub use zmatrix::ZMatrix;
@@ -1,111 +1,422 @@
-use indexmap::IndexMap;
+use anyhow::{bail, ensure};
 use itertools::Itertools;
-use std::{fs::File, path::Path};
+use nalgebra::{DMatrix, Matrix3, Point3, Vector3};
+use std::{fs::File, io::BufReader, path::Path};
 
 use crate::{
-    basis::{BasisSet, ContractedGaussian},
+    basis::{BasisSet, ContractedGaussian, Ecp},
     system::ShellType,
 };
 
-use super::{config_atom::ConfigAtom, shell::Shell, Atom, ShellBasis};
+use super::{
+    config_atom::ConfigMolecule, read_xyz, shell::Shell, write_xyz, Atom, NuclearModel,
+    PointCharge, ShellBasis, XyzFrame,
+};
 
 #[derive(Debug)]
 /// Represents the quantum system of a molecule, represented in some [BasisSet]
 pub struct MolecularSystem<'b> {
     /// The constituent atoms of this system
     pub atoms: Vec<Atom>,
+    /// The total charge of this system
+    pub charge: i32,
+    /// The spin multiplicity 2S + 1 of this system
+    pub multiplicity: usize,
+    /// External point charges that attract the electrons and interact with the nuclei
+    pub external_charges: Vec<PointCharge>,
+    /// The charge distribution of the nuclei in the electron-nuclear attraction
+    pub nuclear_model: NuclearModel,
     /// references to the [ContractedGaussian]s of the [BasisSet] that is used to represent this
-    /// system.
+    /// system. These are the cartesian components of all shells, even of spherical ones.
     pub basis: Vec<&'b ContractedGaussian>,
     /// The [Shell]s that this system has.
     pub(crate) shells: Vec<Shell>,
+    /// The effective core potential of each atom, if its element uses one in the [BasisSet]
+    pub(crate) ecps: Vec<Option<&'b Ecp>>,
+    /// The number of basis functions integral matrices are expressed in
+    n_basis: usize,
 }
 
 impl<'a> MolecularSystem<'a> {
+    /// Loads a system from a JSON file, which is either a list of atoms with positions in Bohr, or
+    /// an object with the `atoms` and optional `units` ("bohr" or "angstrom"), `charge` and
+    /// `multiplicity`. Elements are given by their symbol or ordinal, and each atom may specify a
+    /// custom nuclear `charge` or be marked as `ghost`. Atoms without `element` are bare point
+    /// charges. Further `external_charges` with `charge` and `position` can be given for
+    /// embedding.
     pub fn load(path: impl AsRef<Path>, basis_set: &'a BasisSet) -> anyhow::Result<Self> {
-        let config_atoms: Vec<ConfigAtom> = serde_json::from_reader(File::open(path)?)?;
-        let atoms: Vec<Atom> = config_atoms.into_iter().map(Atom::try_from).try_collect()?;
+        let molecule: ConfigMolecule = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        molecule.into_system(basis_set)
+    }
+
+    /// Loads a system from an XYZ file with a single structure, see [read_xyz].
+    pub fn load_xyz(path: impl AsRef<Path>, basis_set: &'a BasisSet) -> anyhow::Result<Self> {
+        let frames = read_xyz(BufReader::new(File::open(path)?))?;
+        let [frame] = frames.as_slice() else {
+            bail!("expected a single structure, found {}", frames.len());
+        };
+
+        Ok(Self::from_atoms(&frame.atoms, basis_set))
+    }
 
-        Ok(Self::from_atoms(&atoms, basis_set))
+    /// Writes the atoms of this system to an XYZ file, see [write_xyz], which fails for ghost
+    /// atoms and other atoms with custom charges.
+    pub fn save_xyz(&self, path: impl AsRef<Path>, comment: &str) -> anyhow::Result<()> {
+        let frame = XyzFrame {
+            comment: comment.to_owned(),
+            atoms: self.atoms.clone(),
+        };
+        // written to memory first, so that no file is left behind if the atoms are rejected
+        let mut contents = Vec::new();
+        write_xyz(&mut contents, &[frame])?;
+        Ok(std::fs::write(path, contents)?)
     }
 }
 
 impl<'b> MolecularSystem<'b> {
     /// Create a molecular system given the atom types and positons and a basis set.
     /// The basis set must outlive this object.
+    ///
+    /// The system is neutral and in its lowest possible spin state (singlet or doublet), which can
+    /// be changed through the `charge` and `multiplicity` fields.
     pub fn from_atoms(atoms: &[Atom], basis_set: &'b BasisSet) -> Self {
-        let mut shell_map = IndexMap::new();
-
-        // collect basis functions based on shells
-        for (i, atom) in atoms.iter().enumerate() {
-            let atomic_basis = basis_set.atomic_basis(atom);
-
-            for basis_function in atomic_basis {
-                let [l, m, n] = basis_function.angular;
-                let exponents_fp: Vec<i32> = basis_function
-                    .exponents
-                    .iter()
-                    .map(|f| (f * 100.0) as i32)
-                    .collect();
-                shell_map
-                    .entry((i, l + m + n, exponents_fp))
-                    .and_modify(|v: &mut Vec<_>| v.push(basis_function))
-                    .or_insert_with(|| vec![basis_function]);
-            }
-        }
-
-        let mut shells = Vec::with_capacity(shell_map.len());
+        let mut shells = Vec::new();
         let mut basis = Vec::new();
+        let mut n_basis = 0;
 
-        for ((atom_index, angular_magnitude, _), basis_functions) in shell_map {
-            let shell = Shell {
-                shell_type: ShellType(angular_magnitude),
-                atom_index,
-                basis_start_index: basis.len(),
-                basis_size: basis_functions.len(),
-            };
-            shells.push(shell);
-            basis.extend_from_slice(&basis_functions);
-
-            // invariant: basis[start_index..start_index+size] == basis_functions
-            assert_eq!(
-                &basis_functions,
-                &basis[shell.basis_start_index..shell.basis_start_index + shell.basis_size],
-            );
+        for (atom_index, atom) in atoms.iter().enumerate() {
+            for atomic_shell in basis_set.atomic_basis(atom) {
+                let shell = Shell {
+                    shell_type: ShellType(atomic_shell.angular_magnitude),
+                    function_type: atomic_shell.function_type,
+                    atom_index,
+                    basis_start_index: basis.len(),
+                    basis_size: atomic_shell.basis.len(),
+                    matrix_start_index: n_basis,
+                    matrix_size: atomic_shell
+                        .function_type
+                        .count(atomic_shell.angular_magnitude),
+                };
+                shells.push(shell);
+                basis.extend(&atomic_shell.basis);
+                n_basis += shell.matrix_size;
+            }
         }
 
-        log::info!("loaded molecular system with {} atoms and {} basis functions, which were decomposed into {} shells", atoms.len(), basis.len(), shells.len());
+        log::info!("loaded molecular system with {} atoms and {} basis functions, which were decomposed into {} shells", atoms.len(), n_basis, shells.len());
 
-        Self {
+        let mut system = Self {
             atoms: atoms.to_vec(),
+            charge: 0,
+            multiplicity: 1,
+            external_charges: Vec::new(),
+            nuclear_model: NuclearModel::Point,
             basis,
             shells,
-        }
+            ecps: atoms.iter().map(|atom| basis_set.ecp(atom)).collect(),
+            n_basis,
+        };
+        let nuclear_charge = system.electron_charge().round() as usize;
+        system.multiplicity = 1 + nuclear_charge % 2;
+        system
     }
 
+    /// Returns the number of basis functions, i.e., the dimension of integral matrices.
+    /// For basis sets with spherical shells, this is less than the number of (cartesian)
+    /// [ContractedGaussian]s in `basis`.
     pub fn n_basis(&self) -> usize {
-        self.basis.len()
+        self.n_basis
     }
 
     pub fn n_shells(&self) -> usize {
         self.shells.len()
     }
 
+    /// Returns the number of alpha and beta electrons of this system, as given by its charge and
+    /// multiplicity. The charge refers to the atoms with basis functions only, i.e., point charges
+    /// don't contribute electrons. Fails if these are inconsistent with each other.
+    pub fn electron_counts(&self) -> anyhow::Result<(usize, usize)> {
+        let nuclear_charge = self.electron_charge();
+        ensure!(
+            (nuclear_charge - nuclear_charge.round()).abs() < 1e-8,
+            "the total nuclear charge {nuclear_charge} of the atoms with basis functions is not an integer"
+        );
+        let n_electrons = nuclear_charge.round() as i64 - self.charge as i64;
+        ensure!(
+            n_electrons >= 0,
+            "a charge of {} leaves a negative number of electrons",
+            self.charge
+        );
+        ensure!(
+            self.multiplicity >= 1,
+            "the multiplicity has to be positive"
+        );
+
+        let n_unpaired = self.multiplicity as i64 - 1;
+        ensure!(
+            n_unpaired <= n_electrons && (n_electrons - n_unpaired) % 2 == 0,
+            "a multiplicity of {} is impossible with {n_electrons} electrons",
+            self.multiplicity
+        );
+
+        let n_beta = (n_electrons - n_unpaired) / 2;
+        Ok(((n_beta + n_unpaired) as usize, n_beta as usize))
+    }
+
+    /// Returns the charge of the nucleus of the given atom, which is reduced by the number of core
+    /// electrons if the atom has an effective core potential.
+    pub fn nuclear_charge(&self, atom_index: usize) -> f64 {
+        let core_electrons = self.ecp(atom_index).map_or(0, |ecp| ecp.core_electrons);
+        self.atoms[atom_index].nuclear_charge() - core_electrons as f64
+    }
+
+    /// Returns the effective core potential of the given atom, if it has one.
+    pub(crate) fn ecp(&self, atom_index: usize) -> Option<&'b Ecp> {
+        self.ecps.get(atom_index).copied().flatten()
+    }
+
+    /// Returns the number of electrons of the neutral system, i.e., the total nuclear charge of the
+    /// atoms with basis functions.
+    fn electron_charge(&self) -> f64 {
+        (0..self.atoms.len())
+            .filter(|&atom_index| self.atoms[atom_index].has_basis())
+            .map(|atom_index| self.nuclear_charge(atom_index))
+            .sum()
+    }
+
+    /// Returns the electrostatic repulsion energy between the nuclei of this system, including their
+    /// interaction with the external point charges.
+    pub fn nuclear_repulsion_energy(&self) -> f64 {
+        self.charged_pairs()
+            .map(|[(_, charge_a, a), (_, charge_b, b)]| {
+                charge_a * charge_b / nalgebra::distance(&a, &b)
+            })
+            .sum()
+    }
+
+    /// Returns the derivatives of the nuclear repulsion energy with respect to the position of
+    /// each atom.
+    pub fn nuclear_repulsion_gradient(&self) -> Vec<Vector3<f64>> {
+        let mut gradient = vec![Vector3::zeros(); self.atoms.len()];
+        for [(i, charge_a, a), (j, charge_b, b)] in self.charged_pairs() {
+            let diff = a - b;
+            let force = charge_a * charge_b / diff.norm().powi(3) * diff;
+            if let Some(i) = i {
+                gradient[i] -= force;
+            }
+            if let Some(j) = j {
+                gradient[j] += force;
+            }
+        }
+        gradient
+    }
+
+    /// Returns the second derivatives of the nuclear repulsion energy with respect to the atomic
+    /// positions, where row and column 3 * atom + axis belong to the given atom and cartesian axis.
+    pub fn nuclear_repulsion_hessian(&self) -> DMatrix<f64> {
+        let n = 3 * self.atoms.len();
+        let mut hessian = DMatrix::zeros(n, n);
+        for [(i, charge_a, a), (j, charge_b, b)] in self.charged_pairs() {
+            let diff = a - b;
+            let distance = diff.norm();
+            let block = charge_a * charge_b / distance.powi(5)
+                * (3.0 * diff * diff.transpose() - distance.powi(2) * Matrix3::identity());
+
+            for (row, column, sign) in [(i, i, 1.0), (j, j, 1.0), (i, j, -1.0), (j, i, -1.0)] {
+                if let (Some(row), Some(column)) = (row, column) {
+                    let mut target = hessian.fixed_view_mut::<3, 3>(3 * row, 3 * column);
+                    target += sign * block;
+                }
+            }
+        }
+        hessian
+    }
+
+    /// Returns all pairs of interacting charges as (atom index, charge, position), i.e., pairs of
+    /// charged atoms and pairs of a charged atom and an external point charge, whose atom index is
+    /// [None]. Ghost atoms are skipped, so that they may coincide with other charges.
+    fn charged_pairs(&self) -> impl Iterator<Item = [(Option<usize>, f64, Point3<f64>); 2]> + '_ {
+        let atoms = self
+            .atoms
+            .iter()
+            .enumerate()
+            .map(|(i, atom)| (Some(i), self.nuclear_charge(i), atom.position))
+            .filter(|&(_, charge, _)| charge != 0.0);
+        let external = self
+            .external_charges
+            .iter()
+            .map(|point_charge| (None, point_charge.charge, point_charge.position));
+
+        atoms
+            .clone()
+            .tuple_combinations()
+            .chain(atoms.cartesian_product(external))
+            .map(|(a, b)| [a, b])
+    }
+
+    /// Returns the charges, positions and Gaussian exponents (see [NuclearModel]) of everything
+    /// that attracts the electrons, i.e., all atoms (including ghost atoms with zero charge)
+    /// followed by the external point charges.
+    pub(crate) fn attraction_centers(
+        &self,
+    ) -> impl Iterator<Item = (f64, Point3<f64>, Option<f64>)> + '_ {
+        self.atoms
+            .iter()
+            .enumerate()
+            .map(|(i, atom)| {
+                // the nuclear charge distribution is irrelevant if the core is replaced by an
+                // effective core potential
+                let exponent = match self.ecp(i) {
+                    Some(_) => None,
+                    None => self.nuclear_model.exponent(atom),
+                };
+                (self.nuclear_charge(i), atom.position, exponent)
+            })
+            .chain(
+                self.external_charges
+                    .iter()
+                    .map(|point_charge| (point_charge.charge, point_charge.position, None)),
+            )
+    }
+
+    /// Returns the dipole moment of the nuclear charges with respect to the origin.
+    pub fn nuclear_dipole(&self) -> Vector3<f64> {
+        self.atoms
+            .iter()
+            .enumerate()
+            .map(|(i, atom)| self.nuclear_charge(i) * atom.position.coords)
+            .sum()
+    }
+
+    /// Returns the center of the nuclear charges, or [None] if they sum up to zero, e.g. for ghost
+    /// atoms with point charges that compensate each other.
+    pub fn center_of_charge(&self) -> Option<Point3<f64>> {
+        let total_charge: f64 = (0..self.atoms.len()).map(|i| self.nuclear_charge(i)).sum();
+        if total_charge.abs() < 1e-10 {
+            return None;
+        }
+        Some(Point3::from(self.nuclear_dipole() / total_charge))
+    }
+
     /// Get the concrete shell basis of a shell in this system  
-    pub(crate) fn shell_basis(&self, shell_index: usize) -> ShellBasis {
+    pub(crate) fn shell_basis(&self, shell_index: usize) -> ShellBasis<'_> {
         let Shell {
             shell_type,
+            function_type,
             atom_index,
             basis_start_index,
             basis_size,
+            matrix_start_index,
+            matrix_size,
         } = self.shells[shell_index];
 
         ShellBasis {
             shell_type,
+            function_type,
+            atom_index,
             center: self.atoms[atom_index].position,
             basis: &self.basis[basis_start_index..basis_start_index + basis_size],
             start_index: basis_start_index,
             count: basis_size,
+            matrix_start_index,
+            matrix_count: matrix_size,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Point3, Vector3};
+
+    use crate::{basis::BasisSet, system::Atom};
+
+    use super::MolecularSystem;
+
+    #[test]
+    fn nuclear_repulsion_derivatives() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let mut system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+        let gradient = system.nuclear_repulsion_gradient();
+        let hessian = system.nuclear_repulsion_hessian();
+
+        let h = 1e-4;
+        for (atom, atom_gradient) in gradient.iter().enumerate() {
+            for (axis, &analytic) in atom_gradient.iter().enumerate() {
+                let mut displaced = |step: f64| {
+                    system.atoms[atom].position[axis] += step;
+                    let result = (
+                        system.nuclear_repulsion_energy(),
+                        system.nuclear_repulsion_gradient(),
+                    );
+                    system.atoms[atom].position[axis] -= step;
+                    result
+                };
+                let (energy_plus, gradient_plus) = displaced(h);
+                let (energy_minus, gradient_minus) = displaced(-h);
+
+                let expected = (energy_plus - energy_minus) / (2.0 * h);
+                approx::assert_abs_diff_eq!(analytic, expected, epsilon = 1e-7);
+
+                for (other, (plus, minus)) in gradient_plus.iter().zip(&gradient_minus).enumerate()
+                {
+                    let expected: Vector3<f64> = (plus - minus) / (2.0 * h);
+                    let actual = hessian.fixed_view::<1, 3>(3 * atom + axis, 3 * other);
+                    approx::assert_abs_diff_eq!(actual.transpose(), expected, epsilon = 1e-6);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ghost_atoms_and_point_charges() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+
+        let mut atoms = system.atoms.clone();
+        atoms.push(Atom::new(1, Point3::new(0.0, 3.0, 0.0)).ghost());
+        atoms.push(Atom::point_charge(-0.5, Point3::new(0.0, 3.0, 0.0)));
+        let extended = MolecularSystem::from_atoms(&atoms, &basis_set);
+
+        assert_eq!(extended.n_basis(), system.n_basis() + 1);
+        assert_eq!(extended.electron_counts().unwrap(), (5, 5));
+        let point_charge_energy: f64 = system
+            .atoms
+            .iter()
+            .map(|atom| {
+                -0.5 * atom.nuclear_charge()
+                    / nalgebra::distance(&atom.position, &atoms[4].position)
+            })
+            .sum();
+        approx::assert_abs_diff_eq!(
+            extended.nuclear_repulsion_energy(),
+            system.nuclear_repulsion_energy() + point_charge_energy,
+            epsilon = 1e-12
+        );
+        assert_eq!(extended.nuclear_repulsion_gradient()[3], Vector3::zeros());
+    }
+
+    #[test]
+    fn nuclear_dipole() {
+        let basis_set = BasisSet::load("data/basis/STO-3G.json").unwrap();
+        let system = MolecularSystem::load("data/mol/water.json", &basis_set).unwrap();
+
+        let center = system.center_of_charge().unwrap();
+        let origin = Point3::origin();
+        let total_charge = 10.0;
+        approx::assert_abs_diff_eq!(
+            system.nuclear_dipole(),
+            total_charge * (center - origin),
+            epsilon = 1e-12
+        );
+        let gradient_sum: Vector3<f64> = system.nuclear_repulsion_gradient().into_iter().sum();
+        approx::assert_abs_diff_eq!(gradient_sum, Vector3::zeros(), epsilon = 1e-12);
+
+        let atoms = [
+            Atom::new(1, Point3::origin()).ghost(),
+            Atom::point_charge(0.3, Point3::new(0.0, 0.0, 2.0)),
+            Atom::point_charge(-0.3, Point3::new(0.0, 0.0, -2.0)),
+        ];
+        let neutral = MolecularSystem::from_atoms(&atoms, &basis_set);
+        assert!(neutral.center_of_charge().is_none());
+    }
+}
@@ -0,0 +1,40 @@
+use serde::Deserialize;
+
+use crate::periodic_table::ElementType;
+
+use super::Atom;
+
+/// How the charge distribution of the nuclei is modelled in the electron-nuclear attraction.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NuclearModel {
+    /// Each nucleus is a point charge.
+    #[default]
+    Point,
+    /// Each nucleus is a spherical Gaussian charge distribution Z (ζ/π)^(3/2) exp(-ζ r^2), whose
+    /// root-mean-square radius is the standard nuclear radius of the element, see [1]. This
+    /// removes the cusp of the wave function at the nucleus, which matters mostly for heavy
+    /// elements and relativistic methods. Atoms with custom charges and external point charges
+    /// remain point charges, as does the nuclear repulsion, where the difference is negligible.
+    ///
+    /// # References
+    ///
+    /// [1] Visscher, L.; Dyall, K. G. Dirac-Fock atomic electronic structure calculations using
+    /// different nuclear charge distributions. At. Data Nucl. Data Tables 1997, 67, 207–224
+    Gaussian,
+}
+
+impl NuclearModel {
+    /// Returns the exponent ζ of the Gaussian charge distribution of the nucleus of the given atom,
+    /// or [None] for a point charge.
+    pub(crate) fn exponent(&self, atom: &Atom) -> Option<f64> {
+        match self {
+            Self::Point => None,
+            Self::Gaussian if atom.charge().is_some() => None,
+            Self::Gaussian => {
+                let radius = ElementType::from_ordinal(atom.ordinal)?.nuclear_radius();
+                Some(1.5 / radius.powi(2))
+            }
+        }
+    }
+}
@@ -0,0 +1,10 @@
+use nalgebra::Point3;
+
+/// An external point charge, e.g. of a classical environment in QM/MM embedding. External charges
+/// attract the electrons and interact with the nuclei of a
+/// [MolecularSystem](crate::system::MolecularSystem), but not with each other.
+#[derive(Copy, Clone, Debug)]
+pub struct PointCharge {
+    pub charge: f64,
+    pub position: Point3<f64>,
+}
@@ -1,23 +1,31 @@
 use nalgebra::Point3;
 
-use crate::basis::ContractedGaussian;
+use crate::basis::{ContractedGaussian, FunctionType};
 
 /// Represents a shell in a [crate::system::MolecularSystem].
 /// A shell is a collection of basis functions which
 ///  1. have the same [ShellType] (i.e, the same angular momentum magnitude)
 ///  2. are centered on the same atom
+///  3. are the components of the same contraction
 #[derive(Copy, Clone, Debug)]
 pub struct Shell {
     /// The type of this shell
     pub(crate) shell_type: ShellType,
+    /// Whether this shell is made up of cartesian or spherical basis functions
+    pub(crate) function_type: FunctionType,
     /// the index of the [crate::system::Atom] in the [crate::system::MolecularSystem]s atom list
     /// this shell is centered on
     pub(crate) atom_index: usize,
-    /// where in the list of basis functions does this shell "start" (i.e., where is the first of
-    /// this shells basis functions in that list)
+    /// where in the list of (cartesian) basis functions does this shell "start" (i.e., where is
+    /// the first of this shells basis functions in that list)
     pub(super) basis_start_index: usize,
-    /// how many basis functions does this shell contain
+    /// how many (cartesian) basis functions does this shell contain
     pub(super) basis_size: usize,
+    /// where in the rows and columns of integral matrices does this shell start
+    pub(super) matrix_start_index: usize,
+    /// how many rows and columns of integral matrices does this shell span. This is different from
+    /// `basis_size` for spherical shells.
+    pub(super) matrix_size: usize,
 }
 
 /// Represents the basis of a specific shell in the basis of some molecular system.
@@ -28,10 +36,20 @@ pub struct Shell {
 #[derive(Copy, Clone, Debug)]
 pub struct ShellBasis<'b> {
     pub(crate) shell_type: ShellType,
+    pub(crate) function_type: FunctionType,
+    /// index of the atom this shell is centered on
+    pub(crate) atom_index: usize,
     pub(crate) center: Point3<f64>,
+    /// the cartesian basis functions of this shell
     pub(crate) basis: &'b [&'b ContractedGaussian],
+    /// index of the first cartesian basis function of this shell
     pub(crate) start_index: usize,
+    /// number of cartesian basis functions in this shell
     pub(crate) count: usize,
+    /// index of the first row / column this shell occupies in integral matrices
+    pub(crate) matrix_start_index: usize,
+    /// number of rows / columns this shell occupies in integral matrices
+    pub(crate) matrix_count: usize,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
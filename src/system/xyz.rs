@@ -0,0 +1,188 @@
+use std::io::{BufRead, Write};
+
+use anyhow::{bail, Context};
+use itertools::Itertools;
+use nalgebra::Point3;
+
+use crate::periodic_table::ElementType;
+
+use super::Atom;
+
+/// The number of Bohr radii in one Ångström (CODATA 2018)
+pub const ANGSTROM_TO_BOHR: f64 = 1.0 / 0.529177210903;
+
+/// A single structure of an XYZ file
+#[derive(Clone, Debug)]
+pub struct XyzFrame {
+    /// The free-form comment line
+    pub comment: String,
+    /// The atoms, with positions in Bohr
+    pub atoms: Vec<Atom>,
+}
+
+/// Reads all frames of an XYZ file, where positions are given in Ångström and elements either
+/// by their symbol or their atomic number.
+pub fn read_xyz(reader: impl BufRead) -> anyhow::Result<Vec<XyzFrame>> {
+    let mut lines = reader.lines().enumerate();
+    let mut frames = Vec::new();
+
+    while let Some((line_index, line)) = lines.next() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let n_atoms: usize = line
+            .trim()
+            .parse()
+            .with_context(|| format!("line {}: expected the number of atoms", line_index + 1))?;
+
+        let Some((_, comment)) = lines.next() else {
+            bail!("missing comment line after line {}", line_index + 1);
+        };
+        let atoms = (0..n_atoms)
+            .map(|_| {
+                let Some((line_index, line)) = lines.next() else {
+                    bail!("expected {n_atoms} atoms after line {}", line_index + 1);
+                };
+                parse_atom(&line?).with_context(|| format!("line {}", line_index + 1))
+            })
+            .try_collect()?;
+
+        frames.push(XyzFrame {
+            comment: comment?,
+            atoms,
+        });
+    }
+
+    Ok(frames)
+}
+
+/// Writes the given frames in the XYZ format, with positions in Ångström.
+///
+/// The format has no way to store custom nuclear charges, so ghost atoms, point charges and
+/// atoms with a custom charge are rejected rather than written as plain atoms.
+pub fn write_xyz(mut writer: impl Write, frames: &[XyzFrame]) -> anyhow::Result<()> {
+    for (frame_index, frame) in frames.iter().enumerate() {
+        if let Some(index) = frame.atoms.iter().position(|atom| atom.charge().is_some()) {
+            bail!(
+                "atom {index} of frame {frame_index} has a custom charge, which XYZ cannot store"
+            );
+        }
+    }
+    for frame in frames {
+        writeln!(writer, "{}", frame.atoms.len())?;
+        writeln!(writer, "{}", frame.comment.replace('\n', " "))?;
+        for atom in &frame.atoms {
+            let element = ElementType::from_ordinal(atom.ordinal)
+                .with_context(|| format!("unknown atom ordinal {}", atom.ordinal))?;
+            let [x, y, z] = (atom.position.coords / ANGSTROM_TO_BOHR).into();
+            writeln!(
+                writer,
+                "{:<2} {x:18.10} {y:18.10} {z:18.10}",
+                element.symbol()
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Parses a line of the form `<symbol> <x> <y> <z>`, ignoring any further columns.
+fn parse_atom(line: &str) -> anyhow::Result<Atom> {
+    let mut columns = line.split_whitespace();
+    let element = columns.next().context("empty atom line")?;
+    let ordinal = match element.parse::<usize>() {
+        Ok(ordinal) => ordinal,
+        Err(_) => parse_symbol(element)? as usize,
+    };
+
+    let mut position = Point3::origin();
+    for coordinate in position.iter_mut() {
+        let column = columns.next().context("expected three coordinates")?;
+        *coordinate = column
+            .parse::<f64>()
+            .with_context(|| format!("invalid coordinate {column}"))?
+            * ANGSTROM_TO_BOHR;
+    }
+
+    Ok(Atom::new(ordinal, position))
+}
+
+/// Parses an element symbol case-insensitively, as in "Cl", "CL" or "cl".
+pub(super) fn parse_symbol(symbol: &str) -> anyhow::Result<ElementType> {
+    let mut characters = symbol.chars();
+    let normalized = characters.next().map_or(String::new(), |first| {
+        first.to_ascii_uppercase().to_string() + &characters.as_str().to_ascii_lowercase()
+    });
+    ElementType::from_symbol(&normalized).with_context(|| format!("unknown element {symbol}"))
+}
+
+#[cfg(test)]
+mod tests {
+    const WATER: &str = "3
+water, Angstrom
+O   0.000000  -0.075791   0.000000
+H   0.866811   0.601435   0.000000
+h  -0.866811   0.601435   0.000000
+
+2
+hydrogen fluoride
+1 0.0 0.0 0.0
+F 0.0 0.0 0.917 extra columns are ignored
+";
+
+    #[test]
+    fn round_trip() {
+        let frames = super::read_xyz(WATER.as_bytes()).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].comment, "water, Angstrom");
+        assert_eq!(
+            frames[0]
+                .atoms
+                .iter()
+                .map(|atom| atom.ordinal)
+                .collect::<Vec<_>>(),
+            [8, 1, 1]
+        );
+        assert_eq!(frames[1].atoms[1].ordinal, 9);
+        approx::assert_abs_diff_eq!(frames[0].atoms[1].position.x, 1.638036, epsilon = 1e-6);
+
+        let mut written = Vec::new();
+        super::write_xyz(&mut written, &frames).unwrap();
+        let reread = super::read_xyz(written.as_slice()).unwrap();
+        for (frame, reread) in frames.iter().zip(&reread) {
+            assert_eq!(frame.comment, reread.comment);
+            for (atom, reread) in frame.atoms.iter().zip(&reread.atoms) {
+                assert_eq!(atom.ordinal, reread.ordinal);
+                approx::assert_abs_diff_eq!(atom.position, reread.position, epsilon = 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(super::read_xyz("2\ncomment\nH 0 0 0\n".as_bytes()).is_err());
+        assert!(super::read_xyz("1\ncomment\nXx 0 0 0\n".as_bytes()).is_err());
+        assert!(super::read_xyz("1\ncomment\nH 0 0\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn custom_charges() {
+        use super::{Atom, XyzFrame};
+        use nalgebra::Point3;
+
+        let hydrogen = Atom::new(1, Point3::origin());
+        for atom in [
+            hydrogen.ghost(),
+            hydrogen.with_charge(0.5),
+            Atom::point_charge(-1.0, Point3::new(0.0, 0.0, 2.0)),
+        ] {
+            let frame = XyzFrame {
+                comment: String::new(),
+                atoms: vec![hydrogen, atom],
+            };
+            let mut written = Vec::new();
+            assert!(super::write_xyz(&mut written, &[frame]).is_err());
+            assert!(written.is_empty());
+        }
+    }
+}
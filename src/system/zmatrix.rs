@@ -0,0 +1,375 @@
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::{bail, ensure, Context};
+use itertools::Itertools;
+use nalgebra::{Point3, Vector3};
+
+use super::{xyz::parse_symbol, Atom, ANGSTROM_TO_BOHR};
+
+/// A molecular geometry given in internal coordinates, i.e., every atom is placed by its distance
+/// to a previous atom, the angle to a second one and the dihedral angle to a third one.
+///
+/// The format is the common one of, e.g., Gaussian:
+///
+/// ```text
+/// O
+/// H 1 r
+/// H 1 r 2 a
+///
+/// r = 0.96
+/// a = 104.5
+/// ```
+///
+/// Atoms are given by their element symbol, optionally followed by a number to form a label
+/// (e.g. "H2"), or by their ordinal. The dummy atoms "X" and "Du" only serve as references and
+/// don't appear in the resulting atoms. References are either 1-based row numbers or labels of
+/// previous rows, where references that consist of digits only are always row numbers. Lengths
+/// are given in Ångström and angles in degrees, either as numbers or as (optionally negated)
+/// names of variables, which are defined after the first blank line.
+///
+/// Internal coordinates can be changed through [ZMatrix::variables], e.g. to scan a bond length,
+/// after which [ZMatrix::atoms] yields the new geometry.
+#[derive(Clone, Debug)]
+pub struct ZMatrix {
+    rows: Vec<Row>,
+    /// The values of the variables, in Ångström or degrees
+    pub variables: BTreeMap<String, f64>,
+}
+
+/// A single atom of a [ZMatrix]
+#[derive(Clone, Debug)]
+struct Row {
+    /// [None] for dummy atoms
+    ordinal: Option<usize>,
+    /// The references to previous rows along with the bond length, angle and dihedral angle
+    coordinates: Vec<(usize, Value)>,
+}
+
+/// An internal coordinate of a [ZMatrix]
+#[derive(Clone, Debug)]
+enum Value {
+    Constant(f64),
+    Variable { name: String, negated: bool },
+}
+
+impl ZMatrix {
+    /// Reads a [ZMatrix] from a file, see [ZMatrix::parse].
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parses a [ZMatrix] from its text representation, see [ZMatrix] for the format.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .skip_while(|(_, line)| line.trim().is_empty());
+
+        let mut rows = Vec::new();
+        let mut labels = Vec::new();
+        for (line_index, line) in lines.by_ref() {
+            let columns = line
+                .split([' ', '\t', ','])
+                .filter(|c| !c.is_empty())
+                .collect_vec();
+            if columns.is_empty() {
+                break;
+            }
+
+            let row =
+                parse_row(&columns, &labels).with_context(|| format!("line {}", line_index + 1))?;
+            rows.push(row);
+            labels.push(columns[0]);
+        }
+
+        let mut variables = BTreeMap::new();
+        for (line_index, line) in lines {
+            let line = line.trim();
+            if line.is_empty() || line.ends_with(':') {
+                // e.g. "Variables:" or "Constants:"
+                continue;
+            }
+
+            let (name, value) = line
+                .split_once('=')
+                .or_else(|| line.split_once(char::is_whitespace))
+                .with_context(|| {
+                    format!("line {}: expected a variable definition", line_index + 1)
+                })?;
+            let value = value
+                .trim()
+                .parse()
+                .with_context(|| format!("line {}: invalid value {value}", line_index + 1))?;
+            variables.insert(name.trim().to_owned(), value);
+        }
+
+        ensure!(!rows.is_empty(), "the Z-matrix contains no atoms");
+        Ok(Self { rows, variables })
+    }
+
+    /// Returns the atoms with cartesian positions in Bohr, where the first atom is at the origin,
+    /// the second one on the z axis and the third one in the xz plane. Fails if a variable is not
+    /// defined or the references of an angle or dihedral angle don't define it, e.g. if the three
+    /// references of a dihedral angle are collinear.
+    pub fn atoms(&self) -> anyhow::Result<Vec<Atom>> {
+        let mut positions: Vec<Point3<f64>> = Vec::with_capacity(self.rows.len());
+        for (index, row) in self.rows.iter().enumerate() {
+            let values: Vec<f64> = row
+                .coordinates
+                .iter()
+                .map(|(_, value)| self.evaluate(value))
+                .try_collect()?;
+            let references = row
+                .coordinates
+                .iter()
+                .map(|&(reference, _)| positions[reference]);
+
+            let position = match (references.collect_vec().as_slice(), values.as_slice()) {
+                ([], []) => Ok(Point3::origin()),
+                (&[a], &[distance]) => Ok(a + Vector3::z() * distance * ANGSTROM_TO_BOHR),
+                (&[a, b], &[distance, angle]) => {
+                    // any reference that is not collinear with a and b puts the atom in the xz
+                    // plane, as a and b are on the z axis
+                    let c = b + Vector3::x();
+                    place(a, b, c, distance, angle, 0.0)
+                }
+                (&[a, b, c], &[distance, angle, dihedral]) => {
+                    place(a, b, c, distance, angle, dihedral)
+                }
+                _ => unreachable!("the number of coordinates is checked while parsing"),
+            };
+            positions.push(position.with_context(|| format!("row {}", index + 1))?);
+        }
+
+        Ok(self
+            .rows
+            .iter()
+            .zip(positions)
+            .filter_map(|(row, position)| Some(Atom::new(row.ordinal?, position)))
+            .collect())
+    }
+
+    fn evaluate(&self, value: &Value) -> anyhow::Result<f64> {
+        match value {
+            Value::Constant(value) => Ok(*value),
+            Value::Variable { name, negated } => {
+                let value = self
+                    .variables
+                    .get(name)
+                    .with_context(|| format!("undefined variable {name}"))?;
+                Ok(if *negated { -value } else { *value })
+            }
+        }
+    }
+}
+
+/// Parses a row of the form `<element> [<reference> <distance> [<reference> <angle> [<reference>
+/// <dihedral>]]]`, given the labels of the previous rows.
+fn parse_row(columns: &[&str], labels: &[&str]) -> anyhow::Result<Row> {
+    let n_coordinates = labels.len().min(3);
+    ensure!(
+        columns.len() == 1 + 2 * n_coordinates,
+        "expected {n_coordinates} internal coordinates for atom {}",
+        labels.len() + 1
+    );
+
+    let label = columns[0];
+    let ordinal = match label.trim_end_matches(|c: char| c.is_ascii_digit()) {
+        "" => Some(label.parse()?),
+        "X" | "x" | "Du" | "DU" | "du" => None,
+        symbol => Some(parse_symbol(symbol)? as usize),
+    };
+
+    let mut coordinates = Vec::with_capacity(n_coordinates);
+    for (reference, value) in columns[1..].iter().tuples() {
+        // numbers are always row numbers, so that atoms given by their ordinal don't shadow them
+        let reference = if reference.chars().all(|c| c.is_ascii_digit()) {
+            match reference.parse::<usize>() {
+                Ok(row) if (1..=labels.len()).contains(&row) => row - 1,
+                _ => bail!("invalid reference {reference}"),
+            }
+        } else {
+            labels
+                .iter()
+                .position(|label| label == reference)
+                .with_context(|| format!("invalid reference {reference}"))?
+        };
+        ensure!(
+            coordinates.iter().all(|&(other, _)| other != reference),
+            "the references of a row have to be distinct"
+        );
+
+        let value = match value.parse() {
+            Ok(value) => Value::Constant(value),
+            Err(_) => match value.strip_prefix('-') {
+                Some(name) => Value::Variable {
+                    name: name.to_owned(),
+                    negated: true,
+                },
+                None => Value::Variable {
+                    name: value.to_string(),
+                    negated: false,
+                },
+            },
+        };
+        coordinates.push((reference, value));
+    }
+
+    Ok(Row {
+        ordinal,
+        coordinates,
+    })
+}
+
+/// The sine of the angle between the references of a dihedral angle below which they are
+/// considered collinear
+const COLLINEAR_THRESHOLD: f64 = 1e-6;
+
+/// Returns the position of an atom with the given distance (in Ångström) to a, angle (in degrees)
+/// between itself, a and b, and dihedral angle (in degrees) between itself, a, b and c. Fails if
+/// a and b coincide or a, b and c are collinear.
+fn place(
+    a: Point3<f64>,
+    b: Point3<f64>,
+    c: Point3<f64>,
+    distance: f64,
+    angle: f64,
+    dihedral: f64,
+) -> anyhow::Result<Point3<f64>> {
+    let distance = distance * ANGSTROM_TO_BOHR;
+    let (angle, dihedral) = (angle.to_radians(), dihedral.to_radians());
+
+    let ba = (a - b)
+        .try_normalize(0.0)
+        .context("the references of the angle are at the same position")?;
+    let normal = (b - c)
+        .cross(&ba)
+        .try_normalize(COLLINEAR_THRESHOLD * (b - c).norm())
+        .context("the references of the dihedral angle are collinear, consider a dummy atom")?;
+    let in_plane = normal.cross(&ba);
+
+    Ok(a + distance
+        * (-angle.cos() * ba
+            + angle.sin() * dihedral.cos() * in_plane
+            + angle.sin() * dihedral.sin() * normal))
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point3;
+
+    use crate::system::ANGSTROM_TO_BOHR;
+
+    use super::ZMatrix;
+
+    fn angle(a: Point3<f64>, b: Point3<f64>, c: Point3<f64>) -> f64 {
+        (a - b).angle(&(c - b)).to_degrees()
+    }
+
+    /// The dihedral angle a-b-c-d according to the IUPAC convention
+    fn dihedral(a: Point3<f64>, b: Point3<f64>, c: Point3<f64>, d: Point3<f64>) -> f64 {
+        let (b1, b2, b3) = (b - a, c - b, d - c);
+        let (n1, n2) = (b1.cross(&b2), b2.cross(&b3));
+        let x = n1.dot(&n2);
+        let y = n1.cross(&n2).dot(&b2.normalize());
+        y.atan2(x).to_degrees()
+    }
+
+    #[test]
+    fn hydrogen_peroxide() {
+        let mut zmatrix = ZMatrix::parse(
+            "
+            O1
+            O2 O1 roo
+            H3 O1 roh O2 a
+            H4 2 roh 1 a 3 -d
+
+            Variables:
+            roo = 1.45
+            roh 0.97
+            a = 100.0
+            d = 115.0
+            ",
+        )
+        .unwrap();
+        let atoms = zmatrix.atoms().unwrap();
+        let [o1, o2, h3, h4] = [0, 1, 2, 3].map(|i| atoms[i].position);
+
+        assert_eq!(
+            atoms.iter().map(|atom| atom.ordinal).collect::<Vec<_>>(),
+            [8, 8, 1, 1]
+        );
+        approx::assert_abs_diff_eq!(
+            nalgebra::distance(&o1, &o2),
+            1.45 * ANGSTROM_TO_BOHR,
+            epsilon = 1e-12
+        );
+        approx::assert_abs_diff_eq!(
+            nalgebra::distance(&o2, &h4),
+            0.97 * ANGSTROM_TO_BOHR,
+            epsilon = 1e-12
+        );
+        approx::assert_abs_diff_eq!(angle(h3, o1, o2), 100.0, epsilon = 1e-10);
+        approx::assert_abs_diff_eq!(angle(h4, o2, o1), 100.0, epsilon = 1e-10);
+        approx::assert_abs_diff_eq!(dihedral(h4, o2, o1, h3), -115.0, epsilon = 1e-10);
+        approx::assert_abs_diff_eq!(h3.y, 0.0);
+
+        zmatrix.variables.insert("roo".to_owned(), 1.5);
+        let atoms = zmatrix.atoms().unwrap();
+        approx::assert_abs_diff_eq!(
+            nalgebra::distance(&atoms[0].position, &atoms[1].position),
+            1.5 * ANGSTROM_TO_BOHR,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn dummy_atom() {
+        // linear HCN with a dummy atom to define the angles
+        let zmatrix = ZMatrix::parse(
+            "C
+            X 1 1.0
+            N 1 1.16 2 90.0
+            H 1 1.07 2 90.0 3 180.0",
+        )
+        .unwrap();
+        let atoms = zmatrix.atoms().unwrap();
+
+        assert_eq!(atoms.len(), 3);
+        let [c, n, h] = [0, 1, 2].map(|i| atoms[i].position);
+        approx::assert_abs_diff_eq!(angle(n, c, h), 180.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn ordinal_labels() {
+        // the reference 1 is the first row, not the hydrogen atom labelled 1 in the second one
+        let zmatrix = ZMatrix::parse("8\n1 1 0.96\n1 1 0.96 2 104.5").unwrap();
+        let atoms = zmatrix.atoms().unwrap();
+
+        assert_eq!(
+            atoms.iter().map(|atom| atom.ordinal).collect::<Vec<_>>(),
+            [8, 1, 1]
+        );
+        let [o, h1, h2] = [0, 1, 2].map(|i| atoms[i].position);
+        approx::assert_abs_diff_eq!(
+            nalgebra::distance(&o, &h2),
+            0.96 * ANGSTROM_TO_BOHR,
+            epsilon = 1e-12
+        );
+        approx::assert_abs_diff_eq!(angle(h1, o, h2), 104.5, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(ZMatrix::parse("O\nH 2 1.0").is_err());
+        assert!(ZMatrix::parse("O\nH 1 1.0 1 90.0").is_err());
+        assert!(ZMatrix::parse("O\nH 1 1.0\nH 1 1.0 1 90.0").is_err());
+        assert!(ZMatrix::parse("O\nH 1 r").unwrap().atoms().is_err());
+
+        // linear acetylene without a dummy atom, where the dihedral angle of the last hydrogen
+        // is undefined
+        let zmatrix = ZMatrix::parse("C\nC 1 1.2\nH 1 1.06 2 180.0\nH 2 1.06 1 180.0 3 0.0");
+        let error = zmatrix.unwrap().atoms().unwrap_err();
+        assert!(error.to_string().contains("row 4"));
+    }
+}